@@ -0,0 +1,53 @@
+//! Two-thread producer/consumer throughput of the ring buffer.
+//!
+//! To compare with another revision (e.g. before the cache line padding),
+//! run `cargo bench --bench ringbuffer -- --save-baseline before` on that revision,
+//! then `cargo bench --bench ringbuffer -- --baseline before` on this one.
+
+// 这是一个二进制 crate，没有 lib 可以依赖，所以直接引入源码
+#[path = "../src/error.rs"]
+#[allow(dead_code, unused_imports)]
+mod error;
+#[path = "../src/ringbuffer.rs"]
+#[allow(dead_code, unused_imports)]
+mod ringbuffer;
+
+use std::thread;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ringbuffer::ringbuffer;
+
+fn producer_consumer(capacity: usize, count: u64) {
+    let (mut writer, mut reader) = ringbuffer(capacity);
+    let producer = thread::spawn(move || {
+        for i in 0..count {
+            while !writer.push(i) {
+                thread::yield_now();
+            }
+        }
+    });
+
+    let mut received = 0;
+    while received < count {
+        if reader.pop().is_some() {
+            received += 1;
+        } else {
+            thread::yield_now();
+        }
+    }
+    producer.join().unwrap();
+}
+
+fn bench_producer_consumer(c: &mut Criterion) {
+    let count = 1 << 16;
+    let mut group = c.benchmark_group("producer_consumer");
+    group.throughput(Throughput::Elements(count));
+    for capacity in [64, 1024] {
+        group.bench_with_input(BenchmarkId::from_parameter(capacity), &capacity, |b, &capacity| {
+            b.iter(|| producer_consumer(capacity, count));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_producer_consumer);
+criterion_main!(benches);
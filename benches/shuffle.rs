@@ -0,0 +1,76 @@
+//! Throughput of `shuffle_with` against `knuth_shuffle_batched` with the built-in PRNG.
+//!
+//! Run with `cargo bench --bench shuffle`, add `--features rand` to also compare with `rand::rngs::StdRng`.
+
+// 这是一个二进制 crate，没有 lib 可以依赖，所以直接引入源码
+// 除了 knuth_shuffle 依赖的模块，还要引入这些模块的测试中用到的模块
+#[path = "../src/bucketing.rs"]
+#[allow(dead_code, unused_imports)]
+mod bucketing;
+#[path = "../src/choose.rs"]
+#[allow(dead_code, unused_imports)]
+mod choose;
+#[path = "../src/error.rs"]
+#[allow(dead_code, unused_imports)]
+mod error;
+#[path = "../src/knuth_shuffle.rs"]
+#[allow(dead_code, unused_imports)]
+mod knuth_shuffle;
+#[path = "../src/lower_bound.rs"]
+#[allow(dead_code, unused_imports)]
+mod lower_bound;
+#[path = "../src/prng.rs"]
+#[allow(dead_code, unused_imports)]
+mod prng;
+#[path = "../src/ringbuffer.rs"]
+#[allow(dead_code, unused_imports)]
+mod ringbuffer;
+#[path = "../src/rng.rs"]
+#[allow(dead_code, unused_imports)]
+mod rng;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use knuth_shuffle::{knuth_shuffle_batched, shuffle_with};
+use prng::Xoshiro256StarStar;
+
+fn bench_shuffle(c: &mut Criterion) {
+    let mut group = c.benchmark_group("shuffle");
+    for len in [1_000, 1_000_000] {
+        let mut input: Vec<u32> = (0..len).collect();
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        group.throughput(Throughput::Elements(len as u64));
+        group.bench_with_input(BenchmarkId::new("shuffle_with", len), &len, |b, _| {
+            b.iter(|| shuffle_with(&mut input, &mut rng));
+        });
+        group.bench_with_input(BenchmarkId::new("knuth_shuffle_batched", len), &len, |b, _| {
+            b.iter(|| knuth_shuffle_batched(&mut input, &mut rng));
+        });
+    }
+    group.finish();
+}
+
+/// The same comparison with `rand::rngs::StdRng`, whose calls are more expensive.
+#[cfg(feature = "rand")]
+fn bench_shuffle_std_rng(c: &mut Criterion) {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let mut group = c.benchmark_group("shuffle_std_rng");
+    for len in [1_000, 1_000_000] {
+        let mut input: Vec<u32> = (0..len).collect();
+        let mut rng = StdRng::seed_from_u64(1);
+        group.throughput(Throughput::Elements(len as u64));
+        group.bench_with_input(BenchmarkId::new("shuffle_with", len), &len, |b, _| {
+            b.iter(|| shuffle_with(&mut input, &mut rng));
+        });
+        group.bench_with_input(BenchmarkId::new("knuth_shuffle_batched", len), &len, |b, _| {
+            b.iter(|| knuth_shuffle_batched(&mut input, &mut rng));
+        });
+    }
+    group.finish();
+}
+
+#[cfg(not(feature = "rand"))]
+criterion_group!(benches, bench_shuffle);
+#[cfg(feature = "rand")]
+criterion_group!(benches, bench_shuffle, bench_shuffle_std_rng);
+criterion_main!(benches);
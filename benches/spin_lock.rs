@@ -0,0 +1,78 @@
+//! Contended throughput of `SpinLock`, which waits with `Backoff`, against a naive spin lock.
+//!
+//! Run with `cargo bench --bench spin_lock`.
+
+// 这是一个二进制 crate，没有 lib 可以依赖，所以直接引入源码
+#[path = "../src/atomic.rs"]
+#[allow(dead_code, unused_imports)]
+mod atomic;
+
+use std::{
+    cell::UnsafeCell,
+    hint,
+    sync::{atomic::{AtomicBool, Ordering}, Arc},
+    thread,
+};
+use atomic::SpinLock;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+/// The same lock without backoff: it only spins with `spin_loop` hints while the lock is taken.
+struct NaiveSpinLock {
+    locked: AtomicBool,
+    value: UnsafeCell<u64>,
+}
+
+unsafe impl Sync for NaiveSpinLock {}
+
+impl NaiveSpinLock {
+    fn increment(&self) {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            while self.locked.load(Ordering::Relaxed) {
+                hint::spin_loop();
+            }
+        }
+        unsafe { *self.value.get() += 1 };
+        self.locked.store(false, Ordering::Release);
+    }
+}
+
+fn contend<L: Sync + Send + 'static>(lock: Arc<L>, threads: usize, count: u64, increment: fn(&L)) {
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            let lock = lock.clone();
+            thread::spawn(move || {
+                for _ in 0..count {
+                    increment(&lock);
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+fn bench_contended(c: &mut Criterion) {
+    let count = 1 << 12;
+    let mut group = c.benchmark_group("contended_increment");
+    for threads in [2, 4] {
+        group.throughput(Throughput::Elements(count * threads as u64));
+        group.bench_with_input(BenchmarkId::new("backoff", threads), &threads, |b, &threads| {
+            b.iter(|| contend(Arc::new(SpinLock::new(0u64)), threads, count, |lock| *lock.lock() += 1));
+        });
+        group.bench_with_input(BenchmarkId::new("naive", threads), &threads, |b, &threads| {
+            b.iter(|| {
+                let lock = NaiveSpinLock { locked: AtomicBool::new(false), value: UnsafeCell::new(0) };
+                contend(Arc::new(lock), threads, count, NaiveSpinLock::increment)
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_contended);
+criterion_main!(benches);
@@ -0,0 +1,949 @@
+//! Atomic primitives
+
+use std::{
+    cell::UnsafeCell,
+    hint,
+    mem::{self, MaybeUninit},
+    ops::{Deref, DerefMut},
+    sync::atomic::{fence, AtomicBool, AtomicU8, AtomicUsize, Ordering},
+};
+
+/// A one-shot flag, once set it stays set.
+/// e.g. the shutdown signal between the ring buffer writer and reader.
+///
+/// Orderings: setting the flag is a `Release`, observing it is an `Acquire`,
+/// so everything written before `set` is visible after `is_set` returns true.
+#[derive(Debug, Default)]
+pub struct AtomicFlag {
+    flag: AtomicBool,
+}
+
+impl AtomicFlag {
+    pub const fn new() -> Self {
+        Self { flag: AtomicBool::new(false) }
+    }
+
+    /// Set the flag.
+    pub fn set(&self) {
+        self.flag.store(true, Ordering::Release);
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.flag.load(Ordering::Acquire)
+    }
+
+    /// Set the flag if it is unset,
+    /// return whether this call performed the transition.
+    /// 只有一个线程的 CAS 能从 false 变为 true
+    pub fn set_if_unset(&self) -> bool {
+        self.flag
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+}
+
+/// Run an initializer exactly once, even if many threads call `call_once` at the same time.
+///
+/// Callers that lose the race spin, yielding the thread, until the winner finishes `f`,
+/// so `f` should be short. If `f` panics the flag goes back to uninitialized and
+/// the next caller runs its own `f`.
+///
+/// Orderings: finishing is a `Release` and observing it is an `Acquire`,
+/// so everything `f` wrote is visible once `call_once` returns, in every thread.
+#[derive(Debug, Default)]
+pub struct OnceFlag {
+    state: AtomicU8,
+}
+
+impl OnceFlag {
+    const UNINIT: u8 = 0;
+    const INITIALIZING: u8 = 1;
+    const DONE: u8 = 2;
+
+    pub const fn new() -> Self {
+        Self { state: AtomicU8::new(Self::UNINIT) }
+    }
+
+    /// Run `f` if no call has finished yet, otherwise wait for the running one.
+    pub fn call_once<F: FnOnce()>(&self, f: F) {
+        loop {
+            match self.state.compare_exchange(Self::UNINIT, Self::INITIALIZING, Ordering::Acquire, Ordering::Acquire) {
+                Ok(_) => {
+                    // f panic 时 guard 把状态改回 UNINIT，其他线程不会一直等下去
+                    let guard = ResetOnPanic { state: &self.state };
+                    f();
+                    std::mem::forget(guard);
+                    self.state.store(Self::DONE, Ordering::Release);
+                    return;
+                }
+                Err(Self::DONE) => return,
+                Err(_) => {
+                    // 其他线程正在初始化，f 可能较慢，所以让出线程而不是空转
+                    while self.state.load(Ordering::Acquire) == Self::INITIALIZING {
+                        std::thread::yield_now();
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.state.load(Ordering::Acquire) == Self::DONE
+    }
+}
+
+struct ResetOnPanic<'a> {
+    state: &'a AtomicU8,
+}
+
+impl Drop for ResetOnPanic<'_> {
+    fn drop(&mut self) {
+        self.state.store(OnceFlag::UNINIT, Ordering::Release);
+    }
+}
+
+/// A counter for metrics, shared by reference between threads.
+///
+/// Orderings: all operations are `Relaxed`. Every update is still counted exactly,
+/// but the counter does not order other memory, so do not use it to publish data.
+#[derive(Debug, Default)]
+pub struct AtomicCounter {
+    count: AtomicUsize,
+}
+
+impl AtomicCounter {
+    pub const fn new() -> Self {
+        Self { count: AtomicUsize::new(0) }
+    }
+
+    /// Add one, return the new value.
+    pub fn inc(&self) -> usize {
+        self.add(1)
+    }
+
+    /// Subtract one, return the new value. It stays at 0 instead of wrapping around.
+    pub fn dec(&self) -> usize {
+        // 用 CAS 循环实现饱和减法，fetch_sub 在 0 时会回绕
+        match self.count.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| count.checked_sub(1)) {
+            Ok(previous) => previous - 1,
+            Err(_) => 0,
+        }
+    }
+
+    /// Add `n`, return the new value.
+    pub fn add(&self, n: usize) -> usize {
+        self.count.fetch_add(n, Ordering::Relaxed).wrapping_add(n)
+    }
+
+    pub fn get(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Set it back to 0, return the value before.
+    pub fn reset(&self) -> usize {
+        self.count.swap(0, Ordering::Relaxed)
+    }
+}
+
+/// Exponential backoff for spin loops, a lightweight version of crossbeam's `Backoff`.
+///
+/// Each call waits about twice as long as the previous one, so contending threads
+/// stop hammering the same cache line. `snooze` escalates to yielding the thread,
+/// which matters when the thread holding the lock is not running, e.g. on a single core.
+#[derive(Debug, Default)]
+pub struct Backoff {
+    step: u32,
+}
+
+impl Backoff {
+    // spin 最多空转 2^SPIN_LIMIT 次，snooze 超过这个步数后改为让出线程
+    const SPIN_LIMIT: u32 = 6;
+    const YIELD_LIMIT: u32 = 10;
+
+    pub const fn new() -> Self {
+        Self { step: 0 }
+    }
+
+    /// Back off in a lock-free retry loop, e.g. after a failed CAS, only with spin-loop hints.
+    pub fn spin(&mut self) {
+        for _ in 0..1u32 << self.step.min(Self::SPIN_LIMIT) {
+            hint::spin_loop();
+        }
+        if self.step <= Self::SPIN_LIMIT {
+            self.step += 1;
+        }
+    }
+
+    /// Back off while waiting for another thread to make progress, e.g. to release a lock.
+    /// It spins at first, then yields the thread.
+    pub fn snooze(&mut self) {
+        if self.step <= Self::SPIN_LIMIT {
+            for _ in 0..1u32 << self.step {
+                hint::spin_loop();
+            }
+        } else {
+            std::thread::yield_now();
+        }
+        if self.step <= Self::YIELD_LIMIT {
+            self.step += 1;
+        }
+    }
+
+    /// Whether `snooze` has escalated as far as it goes,
+    /// so the caller may rather block, e.g. on a condvar.
+    pub fn is_completed(&self) -> bool {
+        self.step > Self::YIELD_LIMIT
+    }
+
+    pub fn reset(&mut self) {
+        self.step = 0;
+    }
+}
+
+/// A mutex that busy-waits instead of sleeping, for very short critical sections.
+/// While the lock is taken it waits with a `Backoff`, so it ends up yielding under heavy contention.
+///
+/// Orderings: taking the lock is an `Acquire` and releasing it is a `Release`,
+/// so everything written while holding the lock is visible to the next holder.
+#[derive(Debug, Default)]
+pub struct SpinLock<T> {
+    locked: AtomicBool,
+    value: UnsafeCell<T>,
+}
+
+// 和 Mutex 一样，同一时刻只有持有锁的线程能访问 value，所以只要求 T: Send
+unsafe impl<T: Send> Sync for SpinLock<T> {}
+
+impl<T> SpinLock<T> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Spin until the lock is taken, it is released when the guard is dropped.
+    pub fn lock(&self) -> SpinLockGuard<'_, T> {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            // 锁被占用时只读不写，避免反复 CAS 抢占缓存行
+            let mut backoff = Backoff::new();
+            while self.locked.load(Ordering::Relaxed) {
+                backoff.snooze();
+            }
+        }
+        SpinLockGuard { lock: self }
+    }
+
+    /// Take the lock if it is free, without spinning.
+    pub fn try_lock(&self) -> Option<SpinLockGuard<'_, T>> {
+        self.locked
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .ok()
+            .map(|_| SpinLockGuard { lock: self })
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+/// Access to the value of a locked `SpinLock`, see `SpinLock::lock`.
+pub struct SpinLockGuard<'a, T> {
+    lock: &'a SpinLock<T>,
+}
+
+// 只有一个 &SpinLock 字段时，T: Send 就会自动实现 Sync，共享 &guard 会让多个线程同时访问 &T，与 MutexGuard 相同要求 T: Sync
+unsafe impl<T: Sync> Sync for SpinLockGuard<'_, T> {}
+
+impl<T> Deref for SpinLockGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T> DerefMut for SpinLockGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+impl<T> Drop for SpinLockGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.locked.store(false, Ordering::Release);
+    }
+}
+
+/// A spin lock that is taken in arrival order: `lock` takes a ticket from `next_ticket`
+/// and waits until `now_serving` reaches it, dropping the guard serves the next ticket.
+/// Unlike `SpinLock` a waiting thread can't be overtaken, at the price of handing the lock
+/// to a waiter that may not be running, which is slow when there are more threads than cores.
+///
+/// Orderings: waiting for `now_serving` is an `Acquire` and bumping it is a `Release`,
+/// taking a ticket only has to be unique, so it is relaxed.
+#[derive(Debug, Default)]
+pub struct TicketLock<T> {
+    next_ticket: AtomicUsize,
+    now_serving: AtomicUsize,
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send> Sync for TicketLock<T> {}
+
+impl<T> TicketLock<T> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            next_ticket: AtomicUsize::new(0),
+            now_serving: AtomicUsize::new(0),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Wait for our turn, the lock is released when the guard is dropped.
+    pub fn lock(&self) -> TicketLockGuard<'_, T> {
+        // 溢出后回绕，只要同时等待的线程少于 usize::MAX 个就不会混淆
+        let ticket = self.next_ticket.fetch_add(1, Ordering::Relaxed);
+        let mut backoff = Backoff::new();
+        while self.now_serving.load(Ordering::Acquire) != ticket {
+            backoff.snooze();
+        }
+        TicketLockGuard { lock: self }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+/// Access to the value of a locked `TicketLock`, see `TicketLock::lock`.
+pub struct TicketLockGuard<'a, T> {
+    lock: &'a TicketLock<T>,
+}
+
+// 与 SpinLockGuard 相同，共享 &guard 就能访问 &T，所以要求 T: Sync
+unsafe impl<T: Sync> Sync for TicketLockGuard<'_, T> {}
+
+impl<T> Deref for TicketLockGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T> DerefMut for TicketLockGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+impl<T> Drop for TicketLockGuard<'_, T> {
+    fn drop(&mut self) {
+        // 只有持有锁的线程会修改 now_serving，所以不需要 RMW
+        let serving = self.lock.now_serving.load(Ordering::Relaxed);
+        self.lock.now_serving.store(serving.wrapping_add(1), Ordering::Release);
+    }
+}
+
+/// A reader-writer lock that busy-waits: any number of readers or one writer at a time.
+/// Unlike `SeqLock` readers get a reference, so `T` can be any type, but they do write the shared state.
+/// Writers only get in when there are no readers, so a steady stream of readers starves them.
+///
+/// The state is the number of readers times `READER` plus the `WRITER` bit.
+/// Orderings: taking either lock is an `Acquire`, releasing it is a `Release`,
+/// so a reader sees everything the previous writer wrote.
+#[derive(Debug, Default)]
+pub struct RwSpinLock<T> {
+    state: AtomicUsize,
+    value: UnsafeCell<T>,
+}
+
+// 读取端共享 &T，所以还要求 T: Sync，与 RwLock 相同
+unsafe impl<T: Send + Sync> Sync for RwSpinLock<T> {}
+
+impl<T> RwSpinLock<T> {
+    const WRITER: usize = 1;
+    const READER: usize = 2;
+
+    pub const fn new(value: T) -> Self {
+        Self {
+            state: AtomicUsize::new(0),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Spin until no writer holds the lock, then share it with the other readers.
+    pub fn read(&self) -> RwSpinLockReadGuard<'_, T> {
+        let mut backoff = Backoff::new();
+        let mut state = self.state.load(Ordering::Relaxed);
+        loop {
+            if state & Self::WRITER != 0 {
+                backoff.snooze();
+                state = self.state.load(Ordering::Relaxed);
+                continue;
+            }
+            let readers = state.checked_add(Self::READER).expect("Too many readers");
+            match self.state.compare_exchange_weak(state, readers, Ordering::Acquire, Ordering::Relaxed) {
+                Ok(_) => return RwSpinLockReadGuard { lock: self },
+                Err(current) => state = current,
+            }
+        }
+    }
+
+    /// Spin until there are neither readers nor a writer, then take the lock alone.
+    pub fn write(&self) -> RwSpinLockWriteGuard<'_, T> {
+        let mut backoff = Backoff::new();
+        while self
+            .state
+            .compare_exchange_weak(0, Self::WRITER, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            backoff.snooze();
+        }
+        RwSpinLockWriteGuard { lock: self }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+/// Shared access to the value of a `RwSpinLock`, see `RwSpinLock::read`.
+pub struct RwSpinLockReadGuard<'a, T> {
+    lock: &'a RwSpinLock<T>,
+}
+
+impl<T> Deref for RwSpinLockReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T> Drop for RwSpinLockReadGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.state.fetch_sub(RwSpinLock::<T>::READER, Ordering::Release);
+    }
+}
+
+/// Exclusive access to the value of a `RwSpinLock`, see `RwSpinLock::write`.
+pub struct RwSpinLockWriteGuard<'a, T> {
+    lock: &'a RwSpinLock<T>,
+}
+
+impl<T> Deref for RwSpinLockWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T> DerefMut for RwSpinLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+impl<T> Drop for RwSpinLockWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        // 持有写锁时读取端不会修改 state，所以直接清零
+        self.lock.state.store(0, Ordering::Release);
+    }
+}
+
+/// Plain data that `SeqLock` can copy with atomic word loads and stores.
+///
+/// # Safety
+/// Every byte of the type must be initialized, i.e. it has no padding,
+/// otherwise copying it word by word would read uninitialized memory.
+pub unsafe trait NoPadding: Copy {}
+
+macro_rules! impl_no_padding {
+    ($($t:ty),*) => {
+        $(unsafe impl NoPadding for $t {})*
+    };
+}
+
+impl_no_padding!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, bool, char);
+
+unsafe impl<T: NoPadding, const N: usize> NoPadding for [T; N] {}
+
+/// A sequence lock for read-mostly data: readers never block the writer,
+/// they retry instead if a write happened while they were reading.
+///
+/// The value is copied in and out with relaxed atomic loads and stores of whole words,
+/// or of bytes if `T` is not word aligned, so a read racing with a write is not a data race.
+/// Such a read may copy out a torn value, which is thrown away before it is turned into a `T`.
+/// `T: NoPadding` is required because padding bytes can't be loaded through atomics.
+/// Keep `T` small, every retry copies it again.
+///
+/// Orderings: `write` makes the sequence odd, issues a `Release` fence, copies the value in,
+/// then makes the sequence even again with a `Release`. `read` loads the sequence with an `Acquire`
+/// before copying, and after copying it issues an `Acquire` fence and loads it again.
+/// The value is returned only if the two sequences are the same even number, so no write overlapped the copy.
+#[derive(Debug, Default)]
+pub struct SeqLock<T: NoPadding> {
+    seq: AtomicUsize,
+    value: UnsafeCell<T>,
+}
+
+// 读取的值只有在没有并发写入时才会返回，写入之间由 seq 互斥
+unsafe impl<T: NoPadding + Send> Sync for SeqLock<T> {}
+
+impl<T: NoPadding> SeqLock<T> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            seq: AtomicUsize::new(0),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Copy out the value, retrying while a write is in progress.
+    pub fn read(&self) -> T {
+        loop {
+            let before = self.seq.load(Ordering::Acquire);
+            // 奇数表示正在写入
+            if before & 1 == 1 {
+                hint::spin_loop();
+                continue;
+            }
+            // 可能与写入并发，读到的值在确认 seq 不变之前都不能当作 T 使用
+            let value = unsafe { atomic_load(self.value.get()) };
+            fence(Ordering::Acquire);
+            if self.seq.load(Ordering::Relaxed) == before {
+                return unsafe { value.assume_init() };
+            }
+        }
+    }
+
+    /// Replace the value. Concurrent writers are serialized by spinning on the sequence.
+    pub fn write(&self, value: T) {
+        let mut seq = self.seq.load(Ordering::Relaxed);
+        loop {
+            // 把偶数的 seq 改为奇数才能写入，同时排斥其他写入者
+            if seq & 1 == 0 {
+                match self.seq.compare_exchange_weak(seq, seq + 1, Ordering::Acquire, Ordering::Relaxed) {
+                    Ok(_) => break,
+                    Err(current) => seq = current,
+                }
+            } else {
+                hint::spin_loop();
+                seq = self.seq.load(Ordering::Relaxed);
+            }
+        }
+        // 保证读取端看到写入的数据时，也一定能看到奇数的 seq
+        fence(Ordering::Release);
+        unsafe { atomic_store(self.value.get(), &value) };
+        self.seq.store(seq + 2, Ordering::Release);
+    }
+}
+
+/// Whether `T` can be copied as whole `AtomicUsize` words.
+fn by_words<T>() -> bool {
+    mem::align_of::<T>() >= mem::align_of::<AtomicUsize>()
+        && mem::size_of::<T>().is_multiple_of(mem::size_of::<AtomicUsize>())
+}
+
+/// Copy the value out of `src` with relaxed atomic loads, other threads may be storing to it.
+unsafe fn atomic_load<T: NoPadding>(src: *const T) -> MaybeUninit<T> {
+    let mut value = MaybeUninit::<T>::uninit();
+    if by_words::<T>() {
+        let src = src as *const AtomicUsize;
+        let dst = value.as_mut_ptr() as *mut usize;
+        for i in 0..mem::size_of::<T>() / mem::size_of::<usize>() {
+            dst.add(i).write((*src.add(i)).load(Ordering::Relaxed));
+        }
+    } else {
+        let src = src as *const AtomicU8;
+        let dst = value.as_mut_ptr() as *mut u8;
+        for i in 0..mem::size_of::<T>() {
+            dst.add(i).write((*src.add(i)).load(Ordering::Relaxed));
+        }
+    }
+    value
+}
+
+/// Copy `value` into `dst` with relaxed atomic stores, other threads may be loading from it.
+unsafe fn atomic_store<T: NoPadding>(dst: *mut T, value: &T) {
+    // T 没有填充，所以每个字节都已初始化，可以按 usize 或 u8 读出
+    if by_words::<T>() {
+        let src = value as *const T as *const usize;
+        let dst = dst as *const AtomicUsize;
+        for i in 0..mem::size_of::<T>() / mem::size_of::<usize>() {
+            (*dst.add(i)).store(src.add(i).read(), Ordering::Relaxed);
+        }
+    } else {
+        let src = value as *const T as *const u8;
+        let dst = dst as *const AtomicU8;
+        for i in 0..mem::size_of::<T>() {
+            (*dst.add(i)).store(src.add(i).read(), Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, marker::PhantomData, sync::{atomic::{AtomicUsize, Ordering}, Arc}, thread};
+    use super::{AtomicCounter, AtomicFlag, Backoff, OnceFlag, RwSpinLock, SeqLock, SpinLock, SpinLockGuard, TicketLock, TicketLockGuard};
+
+    #[test]
+    fn test_atomic_flag() {
+        let flag = AtomicFlag::new();
+        assert!(!flag.is_set());
+        flag.set();
+        assert!(flag.is_set());
+        flag.set();
+        assert!(flag.is_set());
+        assert!(!flag.set_if_unset());
+    }
+
+    #[test]
+    fn test_observe_across_threads() {
+        let flag = Arc::new(AtomicFlag::new());
+        let data = Arc::new(AtomicUsize::new(0));
+
+        let observers: Vec<_> = (0..4).map(|_| {
+            let flag = flag.clone();
+            let data = data.clone();
+            thread::spawn(move || {
+                while !flag.is_set() {
+                    thread::yield_now();
+                }
+                // set 之前写入的值一定可见
+                assert_eq!(data.load(Ordering::Relaxed), 42);
+            })
+        }).collect();
+
+        data.store(42, Ordering::Relaxed);
+        flag.set();
+        for observer in observers {
+            observer.join().expect("Couldn't join on the associated thread");
+        }
+    }
+
+    #[test]
+    fn test_set_if_unset_wins_once() {
+        let flag = Arc::new(AtomicFlag::new());
+        let winners = Arc::new(AtomicUsize::new(0));
+
+        let threads: Vec<_> = (0..8).map(|_| {
+            let flag = flag.clone();
+            let winners = winners.clone();
+            thread::spawn(move || {
+                if flag.set_if_unset() {
+                    winners.fetch_add(1, Ordering::Relaxed);
+                }
+            })
+        }).collect();
+
+        for t in threads {
+            t.join().expect("Couldn't join on the associated thread");
+        }
+        assert_eq!(winners.load(Ordering::Relaxed), 1);
+        assert!(flag.is_set());
+    }
+
+    #[test]
+    fn test_once_flag() {
+        let once = OnceFlag::new();
+        let mut calls = 0;
+        assert!(!once.is_done());
+        once.call_once(|| calls += 1);
+        once.call_once(|| calls += 1);
+        assert_eq!(calls, 1);
+        assert!(once.is_done());
+    }
+
+    #[test]
+    fn test_once_flag_contended() {
+        let once = Arc::new(OnceFlag::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let data = Arc::new(AtomicUsize::new(0));
+
+        let threads: Vec<_> = (0..8).map(|_| {
+            let once = once.clone();
+            let calls = calls.clone();
+            let data = data.clone();
+            thread::spawn(move || {
+                once.call_once(|| {
+                    calls.fetch_add(1, Ordering::Relaxed);
+                    thread::sleep(std::time::Duration::from_millis(10));
+                    data.store(42, Ordering::Relaxed);
+                });
+                // 返回时初始化的结果一定可见
+                assert_eq!(data.load(Ordering::Relaxed), 42);
+            })
+        }).collect();
+
+        for t in threads {
+            t.join().expect("Couldn't join on the associated thread");
+        }
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_once_flag_panic() {
+        let once = OnceFlag::new();
+        let result = std::panic::catch_unwind(|| once.call_once(|| panic!("init failed")));
+        assert!(result.is_err());
+        assert!(!once.is_done());
+        // panic 之后下一次调用重新初始化
+        let mut calls = 0;
+        once.call_once(|| calls += 1);
+        assert_eq!(calls, 1);
+        assert!(once.is_done());
+    }
+
+    #[test]
+    fn test_atomic_counter() {
+        let counter = AtomicCounter::new();
+        assert_eq!(counter.get(), 0);
+        assert_eq!(counter.inc(), 1);
+        assert_eq!(counter.add(5), 6);
+        assert_eq!(counter.dec(), 5);
+        assert_eq!(counter.reset(), 5);
+        assert_eq!(counter.get(), 0);
+        // 减到 0 之后不再减少
+        assert_eq!(counter.dec(), 0);
+        assert_eq!(counter.get(), 0);
+    }
+
+    #[test]
+    fn test_atomic_counter_across_threads() {
+        let counter = Arc::new(AtomicCounter::new());
+
+        let threads: Vec<_> = (0..8).map(|i| {
+            let counter = counter.clone();
+            thread::spawn(move || {
+                for _ in 0..10_000 {
+                    counter.inc();
+                }
+                counter.add(i);
+            })
+        }).collect();
+
+        for t in threads {
+            t.join().expect("Couldn't join on the associated thread");
+        }
+        assert_eq!(counter.get(), 80_000 + (0..8).sum::<usize>());
+    }
+
+    #[test]
+    fn test_backoff() {
+        let mut backoff = Backoff::new();
+        for _ in 0..=Backoff::YIELD_LIMIT {
+            assert!(!backoff.is_completed());
+            backoff.snooze();
+        }
+        assert!(backoff.is_completed());
+        // 继续调用也不会溢出
+        backoff.snooze();
+        backoff.spin();
+        backoff.reset();
+        assert!(!backoff.is_completed());
+
+        // spin 不会升级到让出线程
+        for _ in 0..100 {
+            backoff.spin();
+        }
+        assert!(!backoff.is_completed());
+    }
+
+    #[test]
+    fn test_spin_lock() {
+        let lock = SpinLock::new(vec![1]);
+        lock.lock().push(2);
+        {
+            let guard = lock.lock();
+            assert_eq!(*guard, vec![1, 2]);
+            // 持有锁时 try_lock 失败
+            assert!(lock.try_lock().is_none());
+        }
+        assert!(lock.try_lock().is_some());
+        assert_eq!(lock.into_inner(), vec![1, 2]);
+    }
+
+    // 类型自己的关联常量优先于 trait 的，但只有 T: Sync 时才存在，否则取到 trait 中的 false，在编译时检查
+    struct IsSync<T>(PhantomData<T>);
+    trait NotSync {
+        const SYNC: bool = false;
+    }
+    impl<T> NotSync for IsSync<T> {}
+    impl<T: Sync> IsSync<T> {
+        const SYNC: bool = true;
+    }
+
+    #[test]
+    fn test_spin_lock_guard_sync() {
+        const { assert!(IsSync::<SpinLock<Cell<i32>>>::SYNC) };
+        const { assert!(IsSync::<SpinLockGuard<'static, i32>>::SYNC) };
+        // 否则两个线程可以通过 &guard 同时修改 Cell
+        const { assert!(!IsSync::<SpinLockGuard<'static, Cell<i32>>>::SYNC) };
+    }
+
+    #[test]
+    fn test_spin_lock_no_lost_updates() {
+        let counter = Arc::new(SpinLock::new(0));
+
+        let threads: Vec<_> = (0..4).map(|_| {
+            let counter = counter.clone();
+            thread::spawn(move || {
+                for _ in 0..10_000 {
+                    // 非原子的读改写，只有锁能保证不丢失更新
+                    *counter.lock() += 1;
+                }
+            })
+        }).collect();
+
+        for t in threads {
+            t.join().expect("Couldn't join on the associated thread");
+        }
+        assert_eq!(*counter.lock(), 40_000);
+    }
+
+    #[test]
+    fn test_ticket_lock() {
+        let lock = Arc::new(TicketLock::new(0));
+
+        let threads: Vec<_> = (0..4).map(|_| {
+            let lock = lock.clone();
+            thread::spawn(move || {
+                for _ in 0..10_000 {
+                    *lock.lock() += 1;
+                }
+            })
+        }).collect();
+
+        for t in threads {
+            t.join().expect("Couldn't join on the associated thread");
+        }
+        assert_eq!(Arc::try_unwrap(lock).unwrap().into_inner(), 40_000);
+    }
+
+    #[test]
+    fn test_ticket_lock_guard_sync() {
+        const { assert!(IsSync::<TicketLock<Cell<i32>>>::SYNC) };
+        const { assert!(IsSync::<TicketLockGuard<'static, i32>>::SYNC) };
+        const { assert!(!IsSync::<TicketLockGuard<'static, Cell<i32>>>::SYNC) };
+    }
+
+    #[test]
+    fn test_ticket_lock_fifo() {
+        let lock = Arc::new(TicketLock::new(Vec::new()));
+        let guard = lock.lock();
+
+        // 持有锁时依次启动线程，等前一个线程拿到号之后再启动下一个
+        let threads: Vec<_> = (0..4).map(|i| {
+            let waiter = lock.clone();
+            let t = thread::spawn(move || waiter.lock().push(i));
+            while lock.next_ticket.load(Ordering::Relaxed) != i + 2 {
+                thread::yield_now();
+            }
+            t
+        }).collect();
+
+        drop(guard);
+        for t in threads {
+            t.join().expect("Couldn't join on the associated thread");
+        }
+        assert_eq!(*lock.lock(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_rw_spin_lock() {
+        let lock = RwSpinLock::new(vec![1]);
+        {
+            // 多个读取端可以同时持有
+            let a = lock.read();
+            let b = lock.read();
+            assert_eq!(*a, *b);
+        }
+        lock.write().push(2);
+        assert_eq!(*lock.read(), vec![1, 2]);
+        assert_eq!(lock.into_inner(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_rw_spin_lock_readers_and_writer() {
+        // 写入端保持两个字段相同，读取端读到不同的值就说明与写入并发了
+        let lock = Arc::new(RwSpinLock::new((0usize, String::from("0"))));
+
+        let readers: Vec<_> = (0..3).map(|_| {
+            let lock = lock.clone();
+            thread::spawn(move || {
+                for _ in 0..10_000 {
+                    let guard = lock.read();
+                    assert_eq!(guard.0.to_string(), guard.1);
+                }
+            })
+        }).collect();
+
+        for i in 1..=1000 {
+            let mut guard = lock.write();
+            guard.0 = i;
+            guard.1 = i.to_string();
+            drop(guard);
+            if i % 100 == 0 {
+                thread::yield_now();
+            }
+        }
+
+        for t in readers {
+            t.join().expect("Couldn't join on the associated thread");
+        }
+        assert_eq!(*lock.read(), (1000, String::from("1000")));
+    }
+
+    #[test]
+    fn test_seq_lock() {
+        let lock = SeqLock::new([1, 2]);
+        assert_eq!(lock.read(), [1, 2]);
+        lock.write([3, 4]);
+        assert_eq!(lock.read(), [3, 4]);
+        // 不按字对齐的类型逐字节复制
+        let lock = SeqLock::new([1u8, 2, 3]);
+        lock.write([4, 5, 6]);
+        assert_eq!(lock.read(), [4, 5, 6]);
+    }
+
+    #[test]
+    fn test_seq_lock_no_torn_reads() {
+        // 每次写入的四个字段相同，读到不同的字段就说明读到了写了一半的值
+        let lock = Arc::new(SeqLock::new([0u64; 4]));
+        let done = Arc::new(AtomicFlag::new());
+
+        let readers: Vec<_> = (0..3).map(|_| {
+            let lock = lock.clone();
+            let done = done.clone();
+            thread::spawn(move || {
+                let mut last = 0;
+                while !done.is_set() {
+                    let value = lock.read();
+                    assert!(value.iter().all(|&x| x == value[0]), "torn read: {:?}", value);
+                    // 写入是递增的，读到的值也不会后退
+                    assert!(value[0] >= last);
+                    last = value[0];
+                    thread::yield_now();
+                }
+            })
+        }).collect();
+
+        // 在 Miri 中运行时减少次数，Miri 会检查读写之间的数据竞争
+        let writes = if cfg!(miri) { 200 } else { 20_000 };
+        for i in 1..=writes {
+            lock.write([i; 4]);
+        }
+        done.set();
+        for reader in readers {
+            reader.join().expect("Couldn't join on the associated thread");
+        }
+        assert_eq!(lock.read(), [writes; 4]);
+    }
+}
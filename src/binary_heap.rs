@@ -0,0 +1,137 @@
+//! Binary heap
+
+use crate::heap_sort::sift_down;
+
+/// A max-heap priority queue stored in a `Vec`.
+/// 下标 i 的子节点是 2 * i + 1 和 2 * i + 2
+#[derive(Debug, Clone)]
+pub struct BinaryHeap<T: Ord> {
+    data: Vec<T>,
+}
+
+impl<T: Ord> BinaryHeap<T> {
+    pub fn new() -> Self {
+        Self { data: Vec::new() }
+    }
+
+    /// Build a heap from the vec in place, O(n).
+    pub fn from_vec(mut data: Vec<T>) -> Self {
+        // 从最后一个非叶子节点开始，自底向上调整
+        for root in (0..data.len() / 2).rev() {
+            sift_down(&mut data, root);
+        }
+        Self { data }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// The greatest element, or `None` if it is empty.
+    pub fn peek(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    /// Push an element, O(log n).
+    pub fn push(&mut self, value: T) {
+        self.data.push(value);
+        self.sift_up(self.data.len() - 1);
+    }
+
+    /// Remove and return the greatest element, O(log n).
+    pub fn pop(&mut self) -> Option<T> {
+        if self.data.is_empty() {
+            return None;
+        }
+        // 把最后一个元素换到堆顶，再向下调整
+        let last = self.data.len() - 1;
+        self.data.swap(0, last);
+        let result = self.data.pop();
+        sift_down(&mut self.data, 0);
+        result
+    }
+
+    /// Consume the heap and return the elements in ascending order, O(n log n).
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        // 与堆排序的后半部分相同
+        for end in (1..self.data.len()).rev() {
+            self.data.swap(0, end);
+            sift_down(&mut self.data[..end], 0);
+        }
+        self.data
+    }
+
+    /// Move the element at `index` up until its parent is not less than it.
+    fn sift_up(&mut self, mut index: usize) {
+        while index > 0 {
+            let parent = (index - 1) / 2;
+            if self.data[parent] >= self.data[index] {
+                break;
+            }
+            self.data.swap(parent, index);
+            index = parent;
+        }
+    }
+}
+
+impl<T: Ord> Default for BinaryHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{prng::Xoshiro256StarStar, rng::Rng};
+    use super::BinaryHeap;
+
+    #[test]
+    fn test_push_pop() {
+        let mut heap = BinaryHeap::new();
+        for value in [3, 1, 4, 1, 5, 9, 2, 6] {
+            heap.push(value);
+        }
+        assert_eq!(heap.len(), 8);
+        assert_eq!(heap.peek(), Some(&9));
+
+        let mut output = Vec::new();
+        while let Some(value) = heap.pop() {
+            output.push(value);
+        }
+        assert_eq!(output, vec![9, 6, 5, 4, 3, 2, 1, 1]);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn test_from_vec() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for len in 0..50 {
+            let input: Vec<u32> = (0..len).map(|_| rng.gen_range(20) as u32).collect();
+            let mut expected = input.clone();
+            expected.sort();
+
+            let mut heap = BinaryHeap::from_vec(input.clone());
+            assert_eq!(heap.peek(), expected.last());
+            let mut output = Vec::new();
+            while let Some(value) = heap.pop() {
+                output.push(value);
+            }
+            output.reverse();
+            assert_eq!(output, expected);
+
+            assert_eq!(BinaryHeap::from_vec(input).into_sorted_vec(), expected);
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        let mut heap: BinaryHeap<i32> = BinaryHeap::default();
+        assert_eq!(heap.peek(), None);
+        assert_eq!(heap.pop(), None);
+        assert_eq!(heap.into_sorted_vec(), vec![]);
+    }
+}
@@ -1,5 +1,8 @@
 //! Binary search algorithm
 
+use std::borrow::Cow;
+use crate::{error::AlgoError, is_sorted::is_sorted, lower_bound::lower_bound};
+
 /// This function implements the binary search algorithm
 /// 二分的基本思路是定义一个搜索区域，逐步收敛区域，区域内的值一定是在逼近搜索值
 pub fn binary_search<T: Ord>(input: &[T], key: T) -> Option<usize> {
@@ -25,6 +28,10 @@ pub fn binary_search<T: Ord>(input: &[T], key: T) -> Option<usize> {
             // 搜索区域右移 [middle+1, high]
             low = middle + 1;
         } else {
+            // middle 为 0 时左侧已经没有值了，不能再减 1，否则会下溢
+            if middle == 0 {
+                return None;
+            }
             // 搜索区域左移 [low, middle-1]
             high = middle - 1;
         }
@@ -34,9 +41,36 @@ pub fn binary_search<T: Ord>(input: &[T], key: T) -> Option<usize> {
     None
 }
 
+/// Same as `binary_search`, but check that `input` is sorted first.
+/// The check is O(n), so it only pays off for untrusted input.
+pub fn binary_search_checked<T: Ord>(input: &[T], key: T) -> Result<Option<usize>, AlgoError> {
+    if !is_sorted(input) {
+        return Err(AlgoError::NotSorted);
+    }
+    Ok(binary_search(input, key))
+}
+
+/// Same as `binary_search`, for a sorted slice padded at the end with `sentinel`.
+/// Only the elements before the first `sentinel` are searched, so `sentinel` itself is never found.
+/// `sentinel` must be no less than every real element, e.g. `i32::MAX`.
+pub fn binary_search_sentinel(input: &[i32], key: i32, sentinel: i32) -> Option<usize> {
+    // 第一个 sentinel 的位置就是逻辑上的结尾，同样用二分查找
+    let end = lower_bound(input, &sentinel);
+    binary_search(&input[..end], key)
+}
+
+/// Same as `binary_search`, for data that may be either borrowed or owned.
+/// 接受 `&Cow` 是为了调用方不用先手动解引用成切片
+#[allow(clippy::ptr_arg)]
+pub fn binary_search_cow<T: Ord + Clone>(input: &Cow<[T]>, key: T) -> Option<usize> {
+    binary_search(input, key)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::binary_search;
+    use std::borrow::Cow;
+    use crate::error::AlgoError;
+    use super::{binary_search, binary_search_checked, binary_search_cow, binary_search_sentinel};
 
     #[test]
     fn test_binary_search() {
@@ -92,4 +126,49 @@ mod tests {
         let input = [0, 1, 2, 3, 3, 3];
         assert_eq!(binary_search(&input, 3), Some(4));
     }
+
+    #[test]
+    fn test_binary_search_not_found() {
+        let input = [1, 3, 5];
+        assert_eq!(binary_search(&input, 0), None);
+        assert_eq!(binary_search(&input, 2), None);
+        assert_eq!(binary_search(&input, 6), None);
+    }
+
+    #[test]
+    fn test_binary_search_checked() {
+        assert_eq!(binary_search_checked(&[1, 2, 2, 5], 5), Ok(Some(3)));
+        assert_eq!(binary_search_checked(&[1, 2, 2, 5], 0), Ok(None));
+        assert_eq!(binary_search_checked(&[] as &[i32], 0), Ok(None));
+        assert_eq!(binary_search_checked(&[1, 3, 2], 3), Err(AlgoError::NotSorted));
+        assert_eq!(binary_search_checked(&[2, 1], 0), Err(AlgoError::NotSorted));
+    }
+
+    #[test]
+    fn test_binary_search_cow() {
+        let data = [1, 3, 5, 7];
+        let borrowed: Cow<[i32]> = Cow::Borrowed(&data);
+        assert_eq!(binary_search_cow(&borrowed, 5), Some(2));
+        assert_eq!(binary_search_cow(&borrowed, 4), None);
+
+        let owned: Cow<[i32]> = Cow::Owned(vec![2, 4, 6]);
+        assert_eq!(binary_search_cow(&owned, 2), Some(0));
+        assert_eq!(binary_search_cow(&owned, 7), None);
+        assert_eq!(binary_search_cow(&Cow::Owned(Vec::new()), 1), None);
+    }
+
+    #[test]
+    fn test_binary_search_sentinel() {
+        let input = [1, 3, 5, i32::MAX, i32::MAX];
+        assert_eq!(binary_search_sentinel(&input, 5, i32::MAX), Some(2));
+        assert_eq!(binary_search_sentinel(&input, 1, i32::MAX), Some(0));
+        assert_eq!(binary_search_sentinel(&input, 4, i32::MAX), None);
+        assert_eq!(binary_search_sentinel(&input, i32::MAX, i32::MAX), None);
+
+        // 没有 sentinel 时搜索整个切片，全是 sentinel 时什么都找不到
+        assert_eq!(binary_search_sentinel(&[1, 3, 5], 5, i32::MAX), Some(2));
+        assert_eq!(binary_search_sentinel(&[i32::MAX; 3], i32::MAX, i32::MAX), None);
+        assert_eq!(binary_search_sentinel(&[], 0, i32::MAX), None);
+        assert_eq!(binary_search_sentinel(&[1, 2, 9, 9], 2, 9), Some(1));
+    }
 }
@@ -0,0 +1,149 @@
+//! Binary search algorithm
+
+use std::cmp::Ordering;
+
+/// This function implements the binary search algorithm with a comparator.
+/// `f` returns the ordering of an element relative to the target.
+/// 找到时返回 `Ok(index)`，多个满足要求的值时逼近左边界
+/// 没找到时返回 `Err(index)`，index 为插入后仍保持有序的位置
+pub fn binary_search_by<T, F: FnMut(&T) -> Ordering>(input: &[T], mut f: F) -> Result<usize, usize> {
+    // 搜索区域是 [low, high)
+    let mut low: usize = 0;
+    let mut high = input.len();
+
+    while low < high {
+        // `low + (high - low) / 2` 就和 `(low + high) / 2` 的结果相同
+        // 但是有效防止了 low 和 high 太大直接相加导致溢出
+        let middle = low + (high - low) / 2;
+
+        if f(&input[middle]) == Ordering::Less {
+            // 搜索区域右移 [middle+1, high)
+            low = middle + 1;
+        } else {
+            // 搜索区域左移 [low, middle)
+            // middle 可能就是要找的值，所以保留在区域的右边界
+            high = middle;
+        }
+    }
+
+    // 此时 low == high，即第一个不小于目标的位置
+    if low < input.len() && f(&input[low]) == Ordering::Equal {
+        Ok(low)
+    } else {
+        Err(low)
+    }
+}
+
+/// Binary search for `key`, and return a reference to the matched element instead of its index.
+/// It is useful when `Ord` only compares part of `T`, e.g. an id, so the element carries more than `key`.
+pub fn binary_search_ref<'a, T: Ord>(input: &'a [T], key: &T) -> Option<&'a T> {
+    binary_search_by(input, |x| x.cmp(key)).ok().map(|index| &input[index])
+}
+
+/// Binary search tuples sorted by the field chosen at runtime, `0`, `1` or `2`, for the first one equal to `key`.
+///
+/// Panics if `field` is greater than 2.
+pub fn binary_search_tuple3(input: &[(i32, i32, i32)], field: usize, key: i32) -> Option<usize> {
+    let get: fn(&(i32, i32, i32)) -> i32 = match field {
+        0 => |x| x.0,
+        1 => |x| x.1,
+        2 => |x| x.2,
+        _ => panic!("field index out of bounds: a 3-tuple has no field {}", field),
+    };
+    binary_search_by(input, |x| get(x).cmp(&key)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+    use super::{binary_search_by, binary_search_ref, binary_search_tuple3};
+
+    /// Only `id` takes part in the comparison.
+    #[derive(Debug)]
+    struct User {
+        id: u32,
+        name: &'static str,
+    }
+
+    impl PartialEq for User {
+        fn eq(&self, other: &Self) -> bool {
+            self.id == other.id
+        }
+    }
+    impl Eq for User {}
+    impl PartialOrd for User {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+    impl Ord for User {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.id.cmp(&other.id)
+        }
+    }
+
+    #[test]
+    fn test_binary_search_by() {
+        let input: [i32; 0] = [];
+        assert_eq!(binary_search_by(&input, |x| x.cmp(&0)), Err(0));
+
+        let input = [1, 3, 5, 7];
+        assert_eq!(binary_search_by(&input, |x| x.cmp(&1)), Ok(0));
+        assert_eq!(binary_search_by(&input, |x| x.cmp(&5)), Ok(2));
+        assert_eq!(binary_search_by(&input, |x| x.cmp(&7)), Ok(3));
+        assert_eq!(binary_search_by(&input, |x| x.cmp(&0)), Err(0));
+        assert_eq!(binary_search_by(&input, |x| x.cmp(&4)), Err(2));
+        assert_eq!(binary_search_by(&input, |x| x.cmp(&8)), Err(4));
+
+        let input = [0, 1, 2, 2, 2, 2, 6];
+        assert_eq!(binary_search_by(&input, |x| x.cmp(&2)), Ok(2));
+
+        let input = [(1, 'a'), (2, 'b'), (4, 'c')];
+        assert_eq!(binary_search_by(&input, |x| x.0.cmp(&4)), Ok(2));
+        assert_eq!(binary_search_by(&input, |x| x.0.cmp(&3)), Err(2));
+    }
+
+    #[test]
+    fn test_binary_search_ref() {
+        let users = [
+            User { id: 1, name: "alice" },
+            User { id: 3, name: "bob" },
+            User { id: 7, name: "carol" },
+        ];
+        // 只需要 id 正确的探针，就能拿到完整的元素
+        let found = binary_search_ref(&users, &User { id: 3, name: "" }).unwrap();
+        assert_eq!(found.name, "bob");
+        assert!(std::ptr::eq(found, &users[1]));
+
+        assert!(binary_search_ref(&users, &User { id: 4, name: "bob" }).is_none());
+        assert_eq!(binary_search_ref(&[1, 2, 3], &3), Some(&3));
+        assert_eq!(binary_search_ref(&[] as &[i32], &3), None);
+    }
+
+    #[test]
+    fn test_binary_search_tuple3() {
+        let mut input = vec![(3, 10, -1), (1, 30, -5), (2, 20, 7), (5, 20, 0)];
+        for field in 0..3 {
+            let get = |x: &(i32, i32, i32)| [x.0, x.1, x.2][field];
+            input.sort_by_key(get);
+            for (index, x) in input.iter().enumerate() {
+                let found = binary_search_tuple3(&input, field, get(x)).unwrap();
+                // 重复值时返回第一个
+                assert_eq!(get(&input[found]), get(x));
+                assert!(found <= index);
+            }
+            assert_eq!(binary_search_tuple3(&input, field, 100), None);
+        }
+
+        input.sort_by_key(|x| x.1);
+        assert_eq!(binary_search_tuple3(&input, 1, 20), Some(1));
+        assert_eq!(binary_search_tuple3(&input, 1, 30), Some(3));
+        assert_eq!(binary_search_tuple3(&[], 2, 0), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_binary_search_tuple3_invalid_field() {
+        binary_search_tuple3(&[(1, 2, 3)], 3, 1);
+    }
+}
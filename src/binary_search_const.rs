@@ -0,0 +1,63 @@
+//! Binary search algorithm
+
+/// This function implements the binary search algorithm in a `const` context.
+/// 与 `binary_search` 的算法相同，只是 trait 方法还不能在 const fn 中调用，所以只支持 i32
+pub const fn binary_search_const(input: &[i32], key: i32) -> Option<usize> {
+    let len = input.len();
+    if len == 0 {
+        return None;
+    }
+
+    // 搜索区域是 [0, len-1]
+    let mut high = len - 1;
+    let mut low: usize = 0;
+
+    while low <= high {
+        // `low + (high - low) / 2` 就和 `(low + high) / 2` 的结果相同
+        // 但是有效防止了 low 和 high 太大直接相加导致溢出
+        let middle = low + (high - low) / 2;
+        let mid_value = input[middle];
+
+        if key == mid_value {
+            // 找到正确值
+            return Some(middle);
+        } else if key > mid_value {
+            // 搜索区域右移 [middle+1, high]
+            low = middle + 1;
+        } else {
+            // middle 为 0 时左侧已经没有值了，不能再减 1，否则在 const 求值时会编译失败
+            if middle == 0 {
+                return None;
+            }
+            // 搜索区域左移 [low, middle-1]
+            high = middle - 1;
+        }
+    }
+
+    // 此时 low > high，故一定没找到值
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::binary_search_const;
+
+    #[test]
+    fn test_binary_search_const() {
+        const RESULT: Option<usize> = binary_search_const(&[1, 3, 5, 7], 5);
+        assert_eq!(RESULT, Some(2));
+
+        const NOT_FOUND: Option<usize> = binary_search_const(&[1, 3, 5, 7], 0);
+        assert_eq!(NOT_FOUND, None);
+
+        const EMPTY: Option<usize> = binary_search_const(&[], 0);
+        assert_eq!(EMPTY, None);
+
+        let input = [0, 1, 2, 3, 4, 5, 6];
+        for key in 0..7 {
+            assert_eq!(binary_search_const(&input, key), Some(key as usize));
+        }
+        assert_eq!(binary_search_const(&input, -1), None);
+        assert_eq!(binary_search_const(&input, 7), None);
+    }
+}
@@ -0,0 +1,127 @@
+//! Binary search algorithm with a SIMD block scan
+
+// 二分把搜索区域缩小到不超过该长度后，整块一次比较
+const BLOCK_LEN: usize = 16;
+
+/// Binary search in a sorted `i32` slice, returning the same index as `binary_search`,
+/// including which one of several equal elements.
+/// It follows the probes of `binary_search` until at most 16 elements are left, then compares
+/// the whole block at once and replays the remaining probes on the positions of the equal run.
+/// The scan uses `std::simd` with the `simd` feature (nightly only), and a plain loop otherwise.
+pub fn binary_search_simd_i32(input: &[i32], key: i32) -> Option<usize> {
+    if input.is_empty() {
+        return None;
+    }
+
+    // 与 `binary_search` 相同，搜索区域是 [low, high]
+    let mut low = 0;
+    let mut high = input.len() - 1;
+    while high - low >= BLOCK_LEN {
+        // 区域至少有 BLOCK_LEN + 1 个元素，middle 不会是 0
+        let middle = low + (high - low) / 2;
+        if key == input[middle] {
+            return Some(middle);
+        } else if key > input[middle] {
+            low = middle + 1;
+        } else {
+            high = middle - 1;
+        }
+    }
+
+    // 块中等于 key 的是连续的一段 [start, end)，不需要再读内存就能知道每个位置的比较结果
+    let (start, end) = scan_block(&input[low..=high], key)?;
+    let mut block_low = 0;
+    let mut block_high = high - low;
+    loop {
+        let middle = block_low + (block_high - block_low) / 2;
+        if middle < start {
+            block_low = middle + 1;
+        } else if middle >= end {
+            // end 至少是 1，middle 不会是 0
+            block_high = middle - 1;
+        } else {
+            // 等于 key 的一段在区域内，`binary_search` 一定会停在其中
+            return Some(low + middle);
+        }
+    }
+}
+
+/// Return the range of `key` in a sorted block of at most `BLOCK_LEN` elements.
+#[cfg(feature = "simd")]
+fn scan_block(block: &[i32], key: i32) -> Option<(usize, usize)> {
+    use std::simd::{cmp::SimdPartialEq, Simd};
+
+    // 不足一块时用 0 补齐，补齐部分的结果会被过滤掉
+    let mut lanes = [0; BLOCK_LEN];
+    lanes[..block.len()].copy_from_slice(block);
+    let mask = Simd::from_array(lanes).simd_eq(Simd::splat(key)).to_bitmask() & ((1 << block.len()) - 1);
+    if mask == 0 {
+        return None;
+    }
+    let start = mask.trailing_zeros() as usize;
+    Some((start, start + mask.count_ones() as usize))
+}
+
+/// Return the range of `key` in a sorted block of at most `BLOCK_LEN` elements.
+#[cfg(not(feature = "simd"))]
+fn scan_block(block: &[i32], key: i32) -> Option<(usize, usize)> {
+    let start = block.iter().position(|&value| value == key)?;
+    let count = block[start..].iter().take_while(|&&value| value == key).count();
+    Some((start, start + count))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{binary_search::binary_search, prng::Xoshiro256StarStar, rng::Rng};
+    use super::binary_search_simd_i32;
+
+    #[test]
+    fn test_binary_search_simd_i32() {
+        assert_eq!(binary_search_simd_i32(&[], 0), None);
+        assert_eq!(binary_search_simd_i32(&[0], 0), Some(0));
+        assert_eq!(binary_search_simd_i32(&[0], -1), None);
+        assert_eq!(binary_search_simd_i32(&[0], 1), None);
+
+        // 补齐的 0 不能被当作匹配
+        let input: Vec<i32> = (1..=20).collect();
+        assert_eq!(binary_search_simd_i32(&input, 0), None);
+        assert_eq!(binary_search_simd_i32(&input, 20), Some(19));
+
+        // 重复时与 `binary_search` 停在同一个下标
+        let input = [0, 1, 2, 2, 2, 2, 6];
+        assert_eq!(binary_search_simd_i32(&input, 2), Some(3));
+        assert_eq!(binary_search(&input, 2), Some(3));
+    }
+
+    #[test]
+    fn test_binary_search_simd_i32_differential() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for len in (1..100).chain([1000, 4096]) {
+            // 非递减且间隔随机，间隔为 0 时产生重复值
+            let mut value = -(rng.gen_range(100) as i32);
+            let input: Vec<i32> = (0..len)
+                .map(|_| {
+                    value += rng.gen_range(3) as i32;
+                    value
+                })
+                .collect();
+            for key in input[0] - 2..=input[len - 1] + 2 {
+                assert_eq!(binary_search_simd_i32(&input, key), binary_search(&input, key), "{:?} {}", input, key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_binary_search_simd_i32_duplicates() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2);
+        for len in [10, 100, 1000] {
+            let mut input: Vec<i32> = (0..len).map(|_| rng.gen_range(20) as i32).collect();
+            input.sort();
+            for key in -1..21 {
+                let found = binary_search_simd_i32(&input, key);
+                assert_eq!(found, binary_search(&input, key));
+                assert_eq!(found.is_some(), input.contains(&key));
+            }
+        }
+    }
+}
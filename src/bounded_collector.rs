@@ -0,0 +1,92 @@
+//! Bounded collector keeping the latest elements of a stream
+
+use std::iter::FromIterator;
+use crate::ringbuffer::RingBuffer;
+
+/// A sink that keeps only the latest `N` elements pushed into it.
+/// `N` must be a power of two, the same as the `RingBuffer` capacity.
+pub struct BoundedCollector<T, const N: usize> {
+    buf: RingBuffer<T>,
+}
+
+impl<T, const N: usize> BoundedCollector<T, N> {
+    /// Panics if `N` is 0 or not a power of two.
+    pub fn new() -> Self {
+        Self { buf: RingBuffer::with_capacity(N) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    /// Push the element, dropping the oldest one if there are already `N` elements.
+    pub fn push(&mut self, value: T) {
+        self.buf.push_overwrite(value);
+    }
+
+    /// Take out the kept elements, from the oldest to the latest.
+    pub fn into_vec(mut self) -> Vec<T> {
+        let mut result = Vec::with_capacity(self.buf.len());
+        while let Some(value) = self.buf.pop() {
+            result.push(value);
+        }
+        result
+    }
+}
+
+impl<T, const N: usize> Default for BoundedCollector<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Extend<T> for BoundedCollector<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for BoundedCollector<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut collector = Self::new();
+        collector.extend(iter);
+        collector
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BoundedCollector;
+
+    #[test]
+    fn test_collect() {
+        let collector: BoundedCollector<i32, 8> = (0..100).collect();
+        assert_eq!(collector.len(), 8);
+        assert_eq!(collector.into_vec(), (92..100).collect::<Vec<i32>>());
+
+        // 不足容量时全部保留
+        let collector: BoundedCollector<i32, 8> = (0..3).collect();
+        assert_eq!(collector.into_vec(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut collector: BoundedCollector<String, 4> = BoundedCollector::new();
+        assert!(collector.is_empty());
+        collector.extend(["a", "b", "c"].iter().map(|s| s.to_string()));
+        collector.extend(["d", "e"].iter().map(|s| s.to_string()));
+        assert_eq!(collector.into_vec(), vec!["b", "c", "d", "e"]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_capacity() {
+        let _: BoundedCollector<i32, 6> = BoundedCollector::new();
+    }
+}
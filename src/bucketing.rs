@@ -0,0 +1,81 @@
+//! Weighted bucketing by cumulative sums
+
+use crate::{lower_bound::partition_point, rng::Rng};
+
+/// Sample bucket indices repeatedly, the probability of each bucket is proportional to its weight.
+/// The cumulative sums are built once, and every sample is a binary search, O(log n).
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightedIndex {
+    // cumulative[i] 是 weights[..=i] 的和，桶 i 对应区间 [cumulative[i-1], cumulative[i])
+    cumulative: Vec<f64>,
+    total: f64,
+    // 最后一个权重不为 0 的桶
+    last: usize,
+}
+
+impl WeightedIndex {
+    /// Panics if the weights are empty, all zero, or contain a negative or non-finite value.
+    pub fn new(weights: &[f64]) -> Self {
+        assert!(
+            weights.iter().all(|w| w.is_finite() && *w >= 0.0),
+            "Weights must be finite and non-negative"
+        );
+
+        let mut total = 0.0;
+        let cumulative: Vec<f64> = weights.iter().map(|w| {
+            total += w;
+            total
+        }).collect();
+        assert!(total > 0.0, "Total weight must be greater than 0");
+
+        let last = weights.iter().rposition(|&w| w > 0.0).unwrap();
+        Self { cumulative, total, last }
+    }
+
+    pub fn total(&self) -> f64 {
+        self.total
+    }
+
+    /// Draw `u` in `[0, total)` and find its bucket.
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> usize {
+        // 找第一个大于 u 的累积和，权重为 0 的桶区间为空，所以不会被选中
+        let u = rng.gen_f64() * self.total;
+        let index = partition_point(&self.cumulative, |&c| c <= u);
+        // 浮点舍入可能使 u 等于 total，此时取最后一个权重不为 0 的桶
+        index.min(self.last)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prng::Xoshiro256StarStar;
+    use super::WeightedIndex;
+
+    #[test]
+    fn test_weighted_index() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        let index = WeightedIndex::new(&[1.0, 0.0, 3.0]);
+        assert_eq!(index.total(), 4.0);
+
+        let mut counts = [0; 3];
+        for _ in 0..40000 {
+            counts[index.sample(&mut rng)] += 1;
+        }
+        // 桶 1 不会被选中，其余约为 1:3
+        assert_eq!(counts[1], 0);
+        assert!((9000..11000).contains(&counts[0]), "{:?}", counts);
+        assert!((29000..31000).contains(&counts[2]), "{:?}", counts);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_all_zero() {
+        WeightedIndex::new(&[0.0, 0.0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_negative() {
+        WeightedIndex::new(&[1.0, -1.0]);
+    }
+}
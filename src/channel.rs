@@ -0,0 +1,186 @@
+//! Bounded multi-producer single-consumer channel on top of the ring buffer
+
+use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+use crate::ringbuffer::{ringbuffer, RingbufferReader, RingbufferWriter};
+
+/// Build a channel holding at most `capacity` messages, it must be a power of two.
+pub fn bounded<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    let (writer, reader) = ringbuffer(capacity);
+    (
+        Sender { shared: Arc::new(Shared { writer, reserved: AtomicUsize::new(0) }) },
+        Receiver { reader },
+    )
+}
+
+/// The ring buffer writer shared by all senders.
+struct Shared<T> {
+    writer: RingbufferWriter<T>,
+    // 下一个可以占用的位置，发送端用 CAS 占位，之后按位置顺序提交
+    reserved: AtomicUsize,
+}
+
+// 发送端只通过 `write_at` 把值移进缓冲区，不会共享 `&T`
+unsafe impl<T: Send> Send for Shared<T> {}
+unsafe impl<T: Send> Sync for Shared<T> {}
+
+/// Sending half of a channel, clone it for more producers.
+/// The channel is closed when the last sender is dropped.
+///
+/// Producers don't take a lock: a send reserves the next position with a CAS on `reserved`,
+/// writes its slot, then commits by moving the tail past it. Commits happen in position order,
+/// so a sender waits for the senders that reserved earlier positions to finish writing.
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Send the message without blocking on the receiver, give it back if the channel is full.
+    /// Messages sent after the receiver is dropped are never received, the channel just fills up.
+    pub fn send(&self, value: T) -> Result<(), T> {
+        let shared = &*self.shared;
+        let mut position = shared.reserved.load(Ordering::Relaxed);
+        loop {
+            // head 只会增加，看到有空位之后这个位置不会再被读取端占用
+            if !shared.writer.has_room_at(position) {
+                // position 可能已经过时，被读取端超过了，重新读一次确认确实满了
+                let current = shared.reserved.load(Ordering::Relaxed);
+                if current == position {
+                    return Err(value);
+                }
+                position = current;
+                continue;
+            }
+            match shared.reserved.compare_exchange_weak(
+                position,
+                position.wrapping_add(1),
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(current) => position = current,
+            }
+        }
+        // CAS 成功后只有当前发送端会写这个位置
+        unsafe { shared.writer.write_at(position, value) };
+        shared.writer.commit_at(position);
+        Ok(())
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Self { shared: self.shared.clone() }
+    }
+}
+
+/// Receiving half of a channel.
+pub struct Receiver<T> {
+    reader: RingbufferReader<T>,
+}
+
+impl<T> Receiver<T> {
+    /// Receive the next message, blocking while the channel is empty and some sender is alive.
+    /// Return `None` once all senders are dropped and every message has been received.
+    pub fn recv(&mut self) -> Option<T> {
+        // 最后一个 sender 析构时 writer 随之析构，读取端由此知道已经关闭
+        self.reader.pop_wait()
+    }
+
+    /// Receive the next message without blocking, `None` if the channel is empty right now.
+    pub fn try_recv(&mut self) -> Option<T> {
+        self.reader.pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{rc::Rc, thread};
+    use super::bounded;
+
+    #[test]
+    fn test_bounded() {
+        let (sender, mut receiver) = bounded(2);
+        assert_eq!(receiver.try_recv(), None);
+        assert_eq!(sender.send(1), Ok(()));
+        assert_eq!(sender.clone().send(2), Ok(()));
+        // 满了之后把消息还回来
+        assert_eq!(sender.send(3), Err(3));
+        assert_eq!(receiver.recv(), Some(1));
+        assert_eq!(sender.send(3), Ok(()));
+        drop(sender);
+        // 关闭之后仍能收到剩余的消息
+        assert_eq!(receiver.recv(), Some(2));
+        assert_eq!(receiver.recv(), Some(3));
+        assert_eq!(receiver.recv(), None);
+    }
+
+    #[test]
+    fn test_multiple_senders() {
+        let (sender, mut receiver) = bounded(16);
+        let threads: Vec<_> = (0..4).map(|t| {
+            let sender = sender.clone();
+            thread::spawn(move || {
+                for i in 0..1000 {
+                    let mut message = t * 1000 + i;
+                    while let Err(back) = sender.send(message) {
+                        message = back;
+                        thread::yield_now();
+                    }
+                }
+            })
+        }).collect();
+        drop(sender);
+
+        let mut received = Vec::new();
+        while let Some(message) = receiver.recv() {
+            received.push(message);
+        }
+        for t in threads {
+            t.join().expect("Couldn't join on the associated thread");
+        }
+        // 同一个 sender 的消息保持顺序
+        for t in 0..4 {
+            let own: Vec<_> = received.iter().filter(|&&m| m / 1000 == t).collect();
+            assert!(own.windows(2).all(|w| w[0] < w[1]));
+        }
+        // 没有丢失也没有重复
+        received.sort();
+        assert_eq!(received, (0..4000).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn test_full_with_multiple_senders() {
+        // 满了之后所有发送端都失败，读取端取走一个后恰好只有一个能成功
+        let (sender, mut receiver) = bounded(4);
+        for i in 0..4 {
+            assert_eq!(sender.send(i), Ok(()));
+        }
+        let senders: Vec<_> = (0..4).map(|_| sender.clone()).collect();
+        for (i, s) in senders.iter().enumerate() {
+            assert_eq!(s.send(10 + i), Err(10 + i));
+        }
+        assert_eq!(receiver.try_recv(), Some(0));
+        let sent: Vec<_> = senders.iter().enumerate().filter(|(i, s)| s.send(10 + i).is_ok()).collect();
+        assert_eq!(sent.len(), 1);
+
+        drop(senders);
+        drop(sender);
+        assert_eq!(receiver.recv(), Some(1));
+        assert_eq!(receiver.recv(), Some(2));
+        assert_eq!(receiver.recv(), Some(3));
+        assert_eq!(receiver.recv(), Some(10));
+        assert_eq!(receiver.recv(), None);
+    }
+
+    #[test]
+    fn test_drop_unreceived() {
+        let value = Rc::new(());
+        let (sender, receiver) = bounded(8);
+        for _ in 0..5 {
+            assert!(sender.send(Rc::clone(&value)).is_ok());
+        }
+        drop(sender);
+        drop(receiver);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+}
@@ -0,0 +1,135 @@
+//! Random choice algorithm
+
+use crate::{bucketing::WeightedIndex, rng::Rng};
+
+/// Return a uniformly random element, or `None` if the input is empty.
+pub fn choose<'a, T, R: Rng>(input: &'a [T], rng: &mut R) -> Option<&'a T> {
+    if input.is_empty() {
+        return None;
+    }
+
+    // gen_range 会丢弃导致取模偏差的值，所以每个下标的概率相同
+    Some(&input[rng.gen_range(input.len())])
+}
+
+/// Return a mutable reference to a uniformly random element, or `None` if the input is empty.
+pub fn choose_mut<'a, T, R: Rng>(input: &'a mut [T], rng: &mut R) -> Option<&'a mut T> {
+    if input.is_empty() {
+        return None;
+    }
+
+    let index = rng.gen_range(input.len());
+    Some(&mut input[index])
+}
+
+/// Return `k` distinct elements chosen uniformly at random.
+/// If the input is shorter than `k`, all elements are returned.
+/// The order of the result is randomized as well.
+pub fn choose_multiple<T: Clone, R: Rng>(input: &[T], k: usize, rng: &mut R) -> Vec<T> {
+    let amount = k.min(input.len());
+
+    // 只对下标做部分 Fisher-Yates，前 amount 个位置就是不重复的均匀采样
+    let len = input.len();
+    let mut indices: Vec<usize> = (0..len).collect();
+    for i in 0..amount {
+        indices.swap(i, i + rng.gen_range(len - i));
+    }
+
+    indices[..amount].iter().map(|&i| input[i].clone()).collect()
+}
+
+/// Return a random index, the probability of each index is proportional to its weight.
+/// Returns `None` if the weights are empty, all zero, or contain a negative or non-finite value.
+pub fn weighted_index<R: Rng>(weights: &[f64], rng: &mut R) -> Option<usize> {
+    if weights.iter().any(|w| !w.is_finite() || *w < 0.0) || weights.iter().all(|&w| w == 0.0) {
+        return None;
+    }
+
+    Some(WeightedIndex::new(weights).sample(rng))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prng::Xoshiro256StarStar;
+    use super::{choose, choose_multiple, choose_mut, weighted_index};
+
+    #[test]
+    fn test_choose_empty() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        let mut input: [i32; 0] = [];
+        assert_eq!(choose(&input, &mut rng), None);
+        assert_eq!(choose_mut(&mut input, &mut rng), None);
+    }
+
+    #[test]
+    fn test_choose_uniform() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        let input = [0, 1, 2, 3];
+        let mut counts = [0; 4];
+        for _ in 0..40000 {
+            counts[*choose(&input, &mut rng).unwrap()] += 1;
+        }
+        // 期望每个值出现 10000 次
+        for count in counts {
+            assert!((9000..11000).contains(&count), "{:?}", counts);
+        }
+    }
+
+    #[test]
+    fn test_choose_mut() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        let mut input = [0, 0, 0];
+        *choose_mut(&mut input, &mut rng).unwrap() = 1;
+        assert_eq!(input.iter().sum::<i32>(), 1);
+    }
+
+    #[test]
+    fn test_choose_multiple_longer_than_input() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        let input = [0, 1, 2];
+        let mut output = choose_multiple(&input, 5, &mut rng);
+        output.sort();
+        assert_eq!(output, vec![0, 1, 2]);
+
+        assert_eq!(choose_multiple(&input, 0, &mut rng), Vec::<i32>::new());
+        assert_eq!(choose_multiple(&[] as &[i32], 3, &mut rng), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn test_choose_multiple_distinct() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        let input: Vec<i32> = (0..20).collect();
+        for _ in 0..100 {
+            let mut output = choose_multiple(&input, 5, &mut rng);
+            assert_eq!(output.len(), 5);
+            output.sort();
+            output.dedup();
+            assert_eq!(output.len(), 5);
+        }
+    }
+
+    #[test]
+    fn test_weighted_index() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        let weights = [1.0, 0.0, 9.0, 0.0];
+        let mut counts = [0; 4];
+        for _ in 0..10000 {
+            counts[weighted_index(&weights, &mut rng).unwrap()] += 1;
+        }
+        // 权重为 0 的下标不会被选中，其余约为 1:9
+        assert_eq!(counts[1], 0);
+        assert_eq!(counts[3], 0);
+        assert!((800..1200).contains(&counts[0]), "{:?}", counts);
+        assert_eq!(counts[0] + counts[2], 10000);
+    }
+
+    #[test]
+    fn test_weighted_index_invalid() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        assert_eq!(weighted_index(&[], &mut rng), None);
+        assert_eq!(weighted_index(&[0.0, 0.0], &mut rng), None);
+        assert_eq!(weighted_index(&[1.0, -1.0], &mut rng), None);
+        assert_eq!(weighted_index(&[1.0, f64::NAN], &mut rng), None);
+        assert_eq!(weighted_index(&[0.0, 2.0], &mut rng), Some(1));
+    }
+}
@@ -0,0 +1,62 @@
+//! Counting sort algorithm
+
+/// Sort integers in `[0, max]` by counting them. O(n + max).
+///
+/// The count array has `max + 1` entries of `usize`, so the memory grows with `max`
+/// rather than with the input, e.g. `max = 255` takes 2 KiB but `max = u32::MAX` would take 32 GiB.
+/// Use it only for small ranges like ages or bytes.
+///
+/// Panics if a value is greater than `max`.
+pub fn counting_sort(input: &mut [u32], max: u32) {
+    let mut counts = vec![0usize; max as usize + 1];
+    for &value in input.iter() {
+        assert!(value <= max, "value {} is greater than max {}", value, max);
+        counts[value as usize] += 1;
+    }
+
+    // 按值从小到大依次写回
+    let mut start = 0;
+    for (value, &count) in counts.iter().enumerate() {
+        input[start..start + count].fill(value as u32);
+        start += count;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{prng::Xoshiro256StarStar, rng::Rng, sort_test_util::check_sort};
+    use super::counting_sort;
+
+    #[test]
+    fn test_counting_sort() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for len in 0..100 {
+            check_sort::<u32>((0..len).map(|_| rng.gen_range(256) as u32).collect(), |input| counting_sort(input, 255));
+        }
+    }
+
+    #[test]
+    fn test_counting_sort_edges() {
+        let mut input = [9, 0, 9, 0, 5];
+        counting_sort(&mut input, 9);
+        assert_eq!(input, [0, 0, 5, 9, 9]);
+
+        let mut input = [3; 10];
+        counting_sort(&mut input, 3);
+        assert_eq!(input, [3; 10]);
+
+        let mut input = [0, 0];
+        counting_sort(&mut input, 0);
+        assert_eq!(input, [0, 0]);
+
+        let mut input: [u32; 0] = [];
+        counting_sort(&mut input, 10);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_counting_sort_out_of_range() {
+        let mut input = [1, 11];
+        counting_sort(&mut input, 10);
+    }
+}
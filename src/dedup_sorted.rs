@@ -0,0 +1,51 @@
+//! Remove duplicates from sorted data
+
+/// Collapse every run of equal elements into its first element, in place.
+/// For sorted input it removes all duplicates. O(n).
+pub fn dedup_sorted<T: PartialEq>(input: &mut Vec<T>) {
+    if input.is_empty() {
+        return;
+    }
+
+    // input[..write] 是已经去重的部分
+    let mut write = 1;
+    for read in 1..input.len() {
+        if input[read] != input[write - 1] {
+            input.swap(read, write);
+            write += 1;
+        }
+    }
+    input.truncate(write);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::dedup_sorted;
+
+    #[test]
+    fn test_dedup_sorted() {
+        let mut input: Vec<i32> = vec![];
+        dedup_sorted(&mut input);
+        assert_eq!(input, vec![]);
+
+        let mut input = vec![7; 10];
+        dedup_sorted(&mut input);
+        assert_eq!(input, vec![7]);
+
+        let mut input = vec![1, 2, 3];
+        dedup_sorted(&mut input);
+        assert_eq!(input, vec![1, 2, 3]);
+
+        let mut input = vec![1, 1, 2, 3, 3, 3, 4, 5, 5];
+        dedup_sorted(&mut input);
+        assert_eq!(input, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_dedup_sorted_keeps_first() {
+        // 每一段保留第一个元素
+        let mut input: Vec<String> = ["a", "a", "b", "b", "a"].iter().map(|s| s.to_string()).collect();
+        dedup_sorted(&mut input);
+        assert_eq!(input, vec!["a", "b", "a"]);
+    }
+}
@@ -0,0 +1,133 @@
+//! Random derangement algorithm
+
+use crate::rng::Rng;
+
+/// Return a uniformly random permutation of `0..len` where no index maps to itself.
+/// Returns an empty vec for `len == 0`.
+///
+/// Panics if `len == 1`, since no derangement exists.
+pub fn derangement<R: Rng>(len: usize, rng: &mut R) -> Vec<usize> {
+    assert_ne!(len, 1, "No derangement exists for a single element");
+
+    let mut result: Vec<usize> = (0..len).collect();
+    'retry: loop {
+        for (i, value) in result.iter_mut().enumerate() {
+            *value = i;
+        }
+
+        // Fisher-Yates 从后往前，第 i 步之后 result[i] 就不会再变
+        // 一旦它成为不动点就提前放弃重来，与整体做完再拒绝的分布相同，所以仍然是均匀的
+        for i in (1..len).rev() {
+            result.swap(i, rng.gen_range(i + 1));
+            if result[i] == i {
+                continue 'retry;
+            }
+        }
+        if len == 0 || result[0] != 0 {
+            return result;
+        }
+    }
+}
+
+/// Shuffle the slice so that no element stays at its original index, uniformly among all such orders.
+///
+/// Panics if `input.len() < 2`.
+pub fn derangement_shuffle<T, R: Rng>(input: &mut [T], rng: &mut R) {
+    let len = input.len();
+    assert!(len >= 2, "A derangement needs at least 2 elements, but the len is {}", len);
+
+    // 新的 input[i] 是原来的 input[perm[i]]，沿着置换的每个环依次交换
+    let perm = derangement(len, rng);
+    let mut visited = vec![false; len];
+    for start in 0..len {
+        let mut current = start;
+        while !visited[current] {
+            visited[current] = true;
+            let next = perm[current];
+            if next == start {
+                break;
+            }
+            input.swap(current, next);
+            current = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::prng::Xoshiro256StarStar;
+    use super::{derangement, derangement_shuffle};
+
+    #[test]
+    fn test_derangement() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        assert_eq!(derangement(0, &mut rng), Vec::<usize>::new());
+        assert_eq!(derangement(2, &mut rng), vec![1, 0]);
+
+        for len in 2..20 {
+            let result = derangement(len, &mut rng);
+            for (i, &value) in result.iter().enumerate() {
+                assert_ne!(i, value);
+            }
+            let mut sorted = result.clone();
+            sorted.sort();
+            assert_eq!(sorted, (0..len).collect::<Vec<usize>>());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_derangement_single() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        derangement(1, &mut rng);
+    }
+
+    #[test]
+    fn test_derangement_uniform() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        let mut counts = HashMap::new();
+        for _ in 0..9000 {
+            *counts.entry(derangement(4, &mut rng)).or_insert(0) += 1;
+        }
+        // 4 个元素共有 9 种错排，期望每种出现 1000 次
+        assert_eq!(counts.len(), 9);
+        for count in counts.values() {
+            assert!((800..1200).contains(count), "{:?}", counts);
+        }
+    }
+
+    #[test]
+    fn test_derangement_shuffle() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for len in 2..50 {
+            let mut input: Vec<String> = (0..len).map(|i| i.to_string()).collect();
+            derangement_shuffle(&mut input, &mut rng);
+            for (i, value) in input.iter().enumerate() {
+                assert_ne!(*value, i.to_string());
+            }
+            let mut sorted: Vec<usize> = input.iter().map(|v| v.parse().unwrap()).collect();
+            sorted.sort();
+            assert_eq!(sorted, (0..len).collect::<Vec<usize>>());
+        }
+
+        let mut input = ['a', 'b'];
+        derangement_shuffle(&mut input, &mut rng);
+        assert_eq!(input, ['b', 'a']);
+    }
+
+    #[test]
+    fn test_derangement_shuffle_matches_indices() {
+        // 与 derangement 返回的下标一致
+        let mut input: Vec<usize> = (0..10).map(|i| i * 10).collect();
+        derangement_shuffle(&mut input, &mut Xoshiro256StarStar::seed_from_u64(7));
+        let perm = derangement(10, &mut Xoshiro256StarStar::seed_from_u64(7));
+        assert_eq!(input, perm.iter().map(|i| i * 10).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_derangement_shuffle_too_short() {
+        derangement_shuffle(&mut [1], &mut Xoshiro256StarStar::seed_from_u64(1));
+    }
+}
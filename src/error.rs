@@ -0,0 +1,64 @@
+//! Error type of the crate
+
+use std::fmt;
+
+/// Errors returned by the `try_` and `_checked` variants instead of panicking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlgoError {
+    /// The capacity is 0, not a power of two, or too large to allocate.
+    InvalidCapacity(usize),
+    /// The input is required to be sorted, but it is not.
+    NotSorted,
+    /// Two inputs are required to have the same length, but they do not.
+    LengthMismatch,
+}
+
+impl fmt::Display for AlgoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlgoError::InvalidCapacity(cap) => write!(f, "capacity {} is not a power of two greater than 0, or too large", cap),
+            AlgoError::NotSorted => write!(f, "input is not sorted"),
+            AlgoError::LengthMismatch => write!(f, "inputs have different lengths"),
+        }
+    }
+}
+
+impl std::error::Error for AlgoError {}
+
+/// Errors returned when parsing a text representation, positions are byte offsets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    /// A char that is not allowed at this position.
+    UnexpectedChar(usize, char),
+    /// The input ended before it was complete.
+    UnexpectedEnd,
+    /// The number starting at this position does not fit the value type.
+    InvalidNumber(usize),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedChar(position, c) => write!(f, "unexpected {:?} at {}", c, position),
+            ParseError::UnexpectedEnd => write!(f, "unexpected end of input"),
+            ParseError::InvalidNumber(position) => write!(f, "invalid number at {}", position),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{AlgoError, ParseError};
+
+    #[test]
+    fn test_display() {
+        assert_eq!(AlgoError::InvalidCapacity(3).to_string(), "capacity 3 is not a power of two greater than 0, or too large");
+        assert_eq!(AlgoError::NotSorted.to_string(), "input is not sorted");
+        assert_eq!(AlgoError::LengthMismatch.to_string(), "inputs have different lengths");
+        assert_eq!(ParseError::UnexpectedChar(3, ')').to_string(), "unexpected ')' at 3");
+        assert_eq!(ParseError::UnexpectedEnd.to_string(), "unexpected end of input");
+        assert_eq!(ParseError::InvalidNumber(0).to_string(), "invalid number at 0");
+    }
+}
@@ -0,0 +1,81 @@
+//! Heap sort algorithm
+
+/// Move `heap[root]` down until both children are not greater than it,
+/// assuming both subtrees of `root` are already max-heaps.
+/// The whole slice is treated as the heap.
+pub fn sift_down<T: Ord>(heap: &mut [T], mut root: usize) {
+    let len = heap.len();
+    loop {
+        // 子节点是 2 * root + 1 和 2 * root + 2
+        let mut child = 2 * root + 1;
+        if child >= len {
+            break;
+        }
+        // 取两个子节点中较大的一个
+        if child + 1 < len && heap[child] < heap[child + 1] {
+            child += 1;
+        }
+        if heap[root] >= heap[child] {
+            break;
+        }
+        heap.swap(root, child);
+        root = child;
+    }
+}
+
+/// Sort the slice with heap sort.
+/// O(n log n) in the worst case and O(1) extra space, not stable.
+pub fn heap_sort<T: Ord>(input: &mut [T]) {
+    let len = input.len();
+    // 从最后一个非叶子节点开始，自底向上建立大顶堆
+    for root in (0..len / 2).rev() {
+        sift_down(input, root);
+    }
+    // 每次把堆顶的最大值换到末尾，然后缩小堆
+    for end in (1..len).rev() {
+        input.swap(0, end);
+        sift_down(&mut input[..end], 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{prng::Xoshiro256StarStar, rng::Rng, sort_test_util::check_sort};
+    use super::{heap_sort, sift_down};
+
+    #[test]
+    fn test_sift_down() {
+        let mut heap = [1, 9, 8, 3, 4, 5];
+        sift_down(&mut heap, 0);
+        assert_eq!(heap, [9, 4, 8, 3, 1, 5]);
+
+        let mut heap: [i32; 0] = [];
+        sift_down(&mut heap, 0);
+    }
+
+    #[test]
+    fn test_heap_sort_random() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for len in 0..100 {
+            check_sort::<i32>((0..len).map(|_| rng.gen_range(50) as i32).collect(), heap_sort);
+        }
+    }
+
+    #[test]
+    fn test_heap_sort_sorted() {
+        let mut input: Vec<i32> = (0..1000).collect();
+        heap_sort(&mut input);
+        assert_eq!(input, (0..1000).collect::<Vec<i32>>());
+
+        let mut input: Vec<i32> = (0..1000).rev().collect();
+        heap_sort(&mut input);
+        assert_eq!(input, (0..1000).collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn test_heap_sort_all_equal() {
+        let mut input = vec![7; 100];
+        heap_sort(&mut input);
+        assert_eq!(input, vec![7; 100]);
+    }
+}
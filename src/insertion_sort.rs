@@ -0,0 +1,67 @@
+//! Insertion sort algorithm
+
+use std::cmp::Ordering;
+
+/// Sort the slice with insertion sort.
+/// Stable. O(n + inversions), so it is fast for tiny or nearly sorted input.
+pub fn insertion_sort<T: Ord>(input: &mut [T]) {
+    insertion_sort_by(input, T::cmp);
+}
+
+/// Same as `insertion_sort`, but compare with `cmp`.
+pub fn insertion_sort_by<T, F: FnMut(&T, &T) -> Ordering>(input: &mut [T], mut cmp: F) {
+    for i in 1..input.len() {
+        // 向左找到第一个不大于 input[i] 的元素，插入到它后面，保持稳定
+        let mut j = i;
+        while j > 0 && cmp(&input[j - 1], &input[i]) == Ordering::Greater {
+            j -= 1;
+        }
+        // 一次整体右移，比逐个交换的写入更少
+        input[j..=i].rotate_right(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{prng::Xoshiro256StarStar, rng::Rng, sort_test_util::check_sort};
+    use super::{insertion_sort, insertion_sort_by};
+
+    #[test]
+    fn test_insertion_sort() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for len in 0..50 {
+            check_sort::<i32>((0..len).map(|_| rng.gen_range(20) as i32).collect(), insertion_sort);
+        }
+    }
+
+    #[test]
+    fn test_insertion_sort_nearly_sorted() {
+        // 有序的输入只需要 n - 1 次比较
+        let mut input: Vec<i32> = (0..10000).collect();
+        let mut comparisons = 0;
+        insertion_sort_by(&mut input, |a, b| {
+            comparisons += 1;
+            a.cmp(b)
+        });
+        assert_eq!(comparisons, 9999);
+
+        // 交换几对相邻元素后，比较次数仍然是线性的
+        for i in (1..10000).step_by(1000) {
+            input.swap(i, i + 1);
+        }
+        let mut comparisons = 0;
+        insertion_sort_by(&mut input, |a, b| {
+            comparisons += 1;
+            a.cmp(b)
+        });
+        assert_eq!(comparisons, 9999 + 10);
+        assert_eq!(input, (0..10000).collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn test_insertion_sort_stable() {
+        let mut input = [(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd'), (0, 'e')];
+        insertion_sort_by(&mut input, |a, b| a.0.cmp(&b.0));
+        assert_eq!(input, [(0, 'e'), (1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')]);
+    }
+}
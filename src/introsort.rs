@@ -0,0 +1,122 @@
+//! Introsort algorithm
+
+use crate::{
+    heap_sort::heap_sort, insertion_sort::insertion_sort, prng::with_thread_rng, quicksort::partition, rng::Rng,
+};
+
+// 小于等于该长度时使用插入排序
+const INSERTION_SORT_THRESHOLD: usize = 16;
+
+/// Sort the slice with introsort.
+/// It is randomized quicksort, but falls back to heap sort when the recursion is too deep,
+/// and uses insertion sort for short slices. Not stable. O(n log n) in the worst case.
+pub fn introsort<T: Ord>(input: &mut [T]) {
+    with_thread_rng(|rng| introsort_with(input, rng));
+}
+
+/// Same as `introsort`, but choose the pivots with `rng`.
+fn introsort_with<T: Ord, R: Rng>(input: &mut [T], rng: &mut R) {
+    // 深度上限是 2 * log2(n)
+    let depth_limit = 2 * (usize::BITS - input.len().leading_zeros()) as usize;
+    sort(input, depth_limit, rng);
+}
+
+fn sort<T: Ord, R: Rng>(mut input: &mut [T], mut depth_limit: usize, rng: &mut R) {
+    // 只递归较短的一侧，较长的一侧留在循环里继续处理
+    loop {
+        if input.len() <= INSERTION_SORT_THRESHOLD {
+            insertion_sort(input);
+            return;
+        }
+        // pivot 选得太差导致深度超限时，改用最坏 O(n log n) 的堆排序
+        if depth_limit == 0 {
+            heap_sort(input);
+            return;
+        }
+        depth_limit -= 1;
+
+        let pivot = rng.gen_range(input.len());
+        let index = partition(input, pivot, &mut T::cmp);
+        let (left, right) = input.split_at_mut(index);
+        let right = &mut right[1..];
+        if left.len() < right.len() {
+            sort(left, depth_limit, rng);
+            input = right;
+        } else {
+            sort(right, depth_limit, rng);
+            input = left;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, cmp::Ordering};
+    use crate::{prng::Xoshiro256StarStar, rng::Rng, sort_test_util::check_sort};
+    use super::{introsort, introsort_with};
+
+    /// `gen_range` always gives 0, so the first element is always the pivot.
+    struct First;
+
+    impl Rng for First {
+        fn next_u64(&mut self) -> u64 {
+            // 返回 0 会被 gen_range 一直拒绝，1 对任意 bound 都得到 0
+            1
+        }
+    }
+
+    /// Count every comparison in `counter`.
+    #[derive(Debug)]
+    struct Counted<'a> {
+        value: u32,
+        counter: &'a Cell<usize>,
+    }
+
+    impl PartialEq for Counted<'_> {
+        fn eq(&self, other: &Self) -> bool {
+            self.cmp(other) == Ordering::Equal
+        }
+    }
+    impl Eq for Counted<'_> {}
+    impl PartialOrd for Counted<'_> {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+    impl Ord for Counted<'_> {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.counter.set(self.counter.get() + 1);
+            self.value.cmp(&other.value)
+        }
+    }
+
+    fn check(input: Vec<i32>) {
+        check_sort(input, introsort);
+    }
+
+    #[test]
+    fn test_introsort() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for len in 0..100 {
+            check((0..len).map(|_| rng.gen_range(50) as i32).collect());
+        }
+        check((0..10000).map(|_| rng.gen_range(1000) as i32).collect());
+        check((0..10000).collect());
+        check((0..10000).rev().collect());
+        check(vec![7; 10000]);
+    }
+
+    #[test]
+    fn test_introsort_adversarial() {
+        // 有序输入且总是选第一个元素作为 pivot，朴素的快速排序需要 O(n^2) 次比较
+        let len = 100_000;
+        let counter = Cell::new(0);
+        let mut input: Vec<Counted> = (0..len).map(|value| Counted { value, counter: &counter }).collect();
+        introsort_with(&mut input, &mut First);
+
+        assert!(input.iter().map(|c| c.value).eq(0..len));
+        // 约 n^2 / 2 = 5e9 次比较时说明退化了，堆排序兜底时远小于 n * log2(n) 的 10 倍
+        let n = len as usize;
+        assert!(counter.get() < 10 * n * 17, "{} comparisons", counter.get());
+    }
+}
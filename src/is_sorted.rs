@@ -0,0 +1,69 @@
+//! Sortedness check
+
+use std::cmp::Ordering;
+
+/// Whether the slice is sorted in ascending order.
+/// Empty and single-element slices are sorted.
+pub fn is_sorted<T: Ord>(input: &[T]) -> bool {
+    is_sorted_by(input, T::cmp)
+}
+
+/// Same as `is_sorted`, but compare with `cmp`.
+pub fn is_sorted_by<T, F: FnMut(&T, &T) -> Ordering>(input: &[T], mut cmp: F) -> bool {
+    // 只要相邻两个元素都不是逆序即可
+    input.windows(2).all(|pair| cmp(&pair[0], &pair[1]) != Ordering::Greater)
+}
+
+/// Return the first index `i` where `input[i] < input[i - 1]`, or `None` if it is sorted.
+/// Handy to find where the data stops being sorted before searching it.
+pub fn first_unsorted<T: Ord>(input: &[T]) -> Option<usize> {
+    input.windows(2).position(|pair| pair[1] < pair[0]).map(|i| i + 1)
+}
+
+/// The length of the longest sorted prefix, so `input[..len]` can be binary searched.
+pub fn sorted_prefix_len<T: Ord>(input: &[T]) -> usize {
+    // 第一个逆序的位置就是前缀的长度
+    first_unsorted(input).unwrap_or(input.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{first_unsorted, is_sorted, is_sorted_by, sorted_prefix_len};
+
+    #[test]
+    fn test_is_sorted() {
+        assert!(is_sorted::<i32>(&[]));
+        assert!(is_sorted(&[1]));
+        assert!(is_sorted(&[1, 2, 2, 3]));
+        assert!(is_sorted(&[5, 5, 5]));
+        assert!(!is_sorted(&[1, 3, 2]));
+        assert!(!is_sorted(&[2, 1]));
+    }
+
+    #[test]
+    fn test_is_sorted_by() {
+        assert!(is_sorted_by(&[3, 2, 2, 1], |a, b| b.cmp(a)));
+        assert!(!is_sorted_by(&[1, 2], |a, b| b.cmp(a)));
+        assert!(is_sorted_by(&["a", "bb", "cc", "ddd"], |a, b| a.len().cmp(&b.len())));
+        assert!(is_sorted_by::<i32, _>(&[], |a, b| a.cmp(b)));
+    }
+
+    #[test]
+    fn test_first_unsorted() {
+        assert_eq!(first_unsorted(&[1, 2, 3]), None);
+        assert_eq!(first_unsorted(&[1, 3, 2, 4]), Some(2));
+        assert_eq!(first_unsorted(&[2, 1, 0]), Some(1));
+        assert_eq!(first_unsorted(&[1, 1, 1]), None);
+        assert_eq!(first_unsorted::<i32>(&[]), None);
+        assert_eq!(first_unsorted(&[1]), None);
+    }
+
+    #[test]
+    fn test_sorted_prefix_len() {
+        assert_eq!(sorted_prefix_len(&[1, 2, 3, 2, 5]), 3);
+        assert_eq!(sorted_prefix_len(&[5, 4, 3]), 1);
+        assert_eq!(sorted_prefix_len(&[1, 2, 2, 7]), 4);
+        assert_eq!(sorted_prefix_len(&[1]), 1);
+        assert_eq!(sorted_prefix_len::<i32>(&[]), 0);
+    }
+}
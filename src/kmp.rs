@@ -0,0 +1,468 @@
+//! KMP string search algorithm
+
+use std::str::Chars;
+
+/// A pattern compiled with its failure table, ready to be searched by KMP.
+/// 模式串和结果下标都以 char 为单位
+pub struct KmpPattern {
+    pattern: Vec<char>,
+    // next[i] 表示 pattern[..=i] 最长的相同前后缀（border）的长度
+    next: Vec<usize>,
+}
+
+/// Summary of the failure table.
+/// The larger the borders, the less KMP can skip on a mismatch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PatternStats {
+    pub max_border: usize,
+    pub total_border: usize,
+}
+
+impl KmpPattern {
+    pub fn new(pattern: &str) -> Self {
+        let pattern: Vec<char> = pattern.chars().collect();
+        let next = Self::build_next(&pattern);
+        Self { pattern, next }
+    }
+
+    /// Build the failure table.
+    fn build_next(pattern: &[char]) -> Vec<usize> {
+        let mut next = vec![0; pattern.len()];
+        // len 是当前已匹配的前缀长度
+        let mut len = 0;
+        for i in 1..pattern.len() {
+            // 失配时回退到更短的 border，直到能继续匹配或退回到 0
+            while len > 0 && pattern[i] != pattern[len] {
+                len = next[len - 1];
+            }
+            if pattern[i] == pattern[len] {
+                len += 1;
+            }
+            next[i] = len;
+        }
+        next
+    }
+
+    /// The failure table used by the search.
+    pub fn next(&self) -> &[usize] {
+        &self.next
+    }
+
+    /// Find the char index of the first match.
+    pub fn find(&self, text: &str) -> Option<usize> {
+        self.find_start(text).map(|(index, _)| index)
+    }
+
+    /// Find the UTF-8 byte offset of the first match.
+    /// The offset can be used directly to slice `text`.
+    pub fn find_byte_offset(&self, text: &str) -> Option<usize> {
+        self.find_start(text).map(|(_, offset)| offset)
+    }
+
+    /// Find the first match, return its char index and byte offset.
+    fn find_start(&self, text: &str) -> Option<(usize, usize)> {
+        if self.pattern.is_empty() {
+            return Some((0, 0));
+        }
+
+        // pattern 在 UTF-8 下的字节长度
+        let pattern_bytes: usize = self.pattern.iter().map(|c| c.len_utf8()).sum();
+        // matched 是 pattern 已匹配的长度
+        let mut matched = 0;
+        for (i, (offset, c)) in text.char_indices().enumerate() {
+            while matched > 0 && c != self.pattern[matched] {
+                matched = self.next[matched - 1];
+            }
+            if c == self.pattern[matched] {
+                matched += 1;
+            }
+            if matched == self.pattern.len() {
+                return Some((i + 1 - matched, offset + c.len_utf8() - pattern_bytes));
+            }
+        }
+
+        None
+    }
+
+    /// Find the first match in `text` treated as circular, so a match may wrap from the end to the start.
+    /// Return the char index in `text` where it starts.
+    /// A pattern longer than `text` never matches, since it would have to reuse chars.
+    pub fn find_circular(&self, text: &str) -> Option<usize> {
+        if self.pattern.is_empty() {
+            return Some(0);
+        }
+        let len = text.chars().count();
+        if self.pattern.len() > len {
+            return None;
+        }
+
+        // 相当于在 text 后面再接上 text 的前 pattern.len() - 1 个 char
+        let chars = text.chars().chain(text.chars().take(self.pattern.len() - 1));
+        let mut matched = 0;
+        for (i, c) in chars.enumerate() {
+            while matched > 0 && c != self.pattern[matched] {
+                matched = self.next[matched - 1];
+            }
+            if c == self.pattern[matched] {
+                matched += 1;
+            }
+            if matched == self.pattern.len() {
+                return Some(i + 1 - matched);
+            }
+        }
+
+        None
+    }
+
+    /// Iterate over the char indices of all matches, overlapping ones included.
+    /// An empty pattern matches at every char index, `0` to the number of chars.
+    pub fn find_iter<'p, 't>(&'p self, text: &'t str) -> Matches<'p, 't> {
+        Matches {
+            pattern: self,
+            chars: text.chars(),
+            position: 0,
+            matched: 0,
+            done: false,
+        }
+    }
+
+    /// Summarize the failure table.
+    pub fn stats(&self) -> PatternStats {
+        PatternStats {
+            max_border: self.next.iter().copied().max().unwrap_or(0),
+            total_border: self.next.iter().sum(),
+        }
+    }
+}
+
+/// Iterator over the matches of a `KmpPattern`, see `KmpPattern::find_iter`.
+pub struct Matches<'p, 't> {
+    pattern: &'p KmpPattern,
+    chars: Chars<'t>,
+    // 已经读取的 char 数量
+    position: usize,
+    matched: usize,
+    // 只用于空模式串，读完所有 char 之后结束
+    done: bool,
+}
+
+impl Iterator for Matches<'_, '_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let pattern = &self.pattern.pattern;
+        let next = &self.pattern.next;
+        if pattern.is_empty() {
+            if self.done {
+                return None;
+            }
+            let position = self.position;
+            match self.chars.next() {
+                Some(_) => self.position += 1,
+                None => self.done = true,
+            }
+            return Some(position);
+        }
+
+        for c in &mut self.chars {
+            self.position += 1;
+            while self.matched > 0 && c != pattern[self.matched] {
+                self.matched = next[self.matched - 1];
+            }
+            if c == pattern[self.matched] {
+                self.matched += 1;
+            }
+            if self.matched == pattern.len() {
+                let start = self.position - self.matched;
+                // 回退到最长的 border 继续匹配，这样重叠的匹配也能找到
+                self.matched = next[self.matched - 1];
+                return Some(start);
+            }
+        }
+
+        None
+    }
+}
+
+/// This function implements the KMP search algorithm
+/// 返回第一次匹配的 char 下标
+pub fn kmp_search(text: &str, pattern: &str) -> Option<usize> {
+    KmpPattern::new(pattern).find(text)
+}
+
+/// Same as `kmp_search`, but return the UTF-8 byte offset of the first match.
+pub fn kmp_search_byte_offset(text: &str, pattern: &str) -> Option<usize> {
+    KmpPattern::new(pattern).find_byte_offset(text)
+}
+
+/// Same as `kmp_search`, but treat `text` as circular so a match may wrap around its end.
+/// 返回的 char 下标在 `0..text.chars().count()` 中
+pub fn kmp_search_circular(text: &str, pattern: &str) -> Option<usize> {
+    KmpPattern::new(pattern).find_circular(text)
+}
+
+/// Return the char indices of the first `max` matches, overlapping ones included.
+/// The search is lazy, so the text after the `max`-th match is not scanned.
+pub fn kmp_find_n(text: &str, pattern: &str, max: usize) -> Vec<usize> {
+    KmpPattern::new(pattern).find_iter(text).take(max).collect()
+}
+
+/// Call `f` with the char index of every match as soon as it is found, overlapping ones included.
+/// Nothing is allocated besides the compiled pattern.
+pub fn kmp_for_each_match<F: FnMut(usize)>(text: &str, pattern: &str, f: F) {
+    KmpPattern::new(pattern).find_iter(text).for_each(f);
+}
+
+/// The length in chars of the longest suffix of `a` that is also a prefix of `b`.
+/// Useful to stitch overlapping chunks: `b` can be appended to `a` without its first `overlap_len(a, b)` chars.
+pub fn overlap_len(a: &str, b: &str) -> usize {
+    let pattern = KmpPattern::new(b);
+    if pattern.pattern.is_empty() {
+        return 0;
+    }
+
+    // 用 b 去匹配 a，扫描完 a 后已匹配的长度就是 a 的后缀与 b 的前缀重叠的长度
+    let mut matched = 0;
+    for c in a.chars() {
+        // b 已完整匹配时同样回退，才能继续向后匹配
+        if matched == pattern.pattern.len() {
+            matched = pattern.next[matched - 1];
+        }
+        while matched > 0 && c != pattern.pattern[matched] {
+            matched = pattern.next[matched - 1];
+        }
+        if c == pattern.pattern[matched] {
+            matched += 1;
+        }
+    }
+    matched
+}
+
+/// Return the first position where `pattern` matches `text` with at most one differing byte.
+/// A window matches if its longest common prefix and suffix with `pattern` cover all but one byte,
+/// both are read from Z-arrays, so it runs in O(n + m).
+pub fn search_one_mismatch(text: &[u8], pattern: &[u8]) -> Option<usize> {
+    let (n, m) = (text.len(), pattern.len());
+    if m > n {
+        return None;
+    }
+    if m == 0 {
+        return Some(0);
+    }
+
+    // 拼接后 z[m + i] 至少与 pattern 本身一样长时就截断为 m，所以不需要分隔符
+    let forward: Vec<u8> = pattern.iter().chain(text).copied().collect();
+    let backward: Vec<u8> = pattern.iter().rev().chain(text.iter().rev()).copied().collect();
+    let prefix = z_array(&forward);
+    let suffix = z_array(&backward);
+    (0..=n - m).find(|&i| {
+        // 在 i 开始的窗口中，与 pattern 相同的最长前缀和最长后缀
+        let common_prefix = prefix[m + i].min(m);
+        let common_suffix = suffix[m + (n - i - m)].min(m);
+        common_prefix + common_suffix + 1 >= m
+    })
+}
+
+/// z[i] is the length of the longest common prefix of `s` and `s[i..]`, z[0] is `s.len()`.
+fn z_array(s: &[u8]) -> Vec<usize> {
+    let mut z = vec![0; s.len()];
+    if s.is_empty() {
+        return z;
+    }
+    z[0] = s.len();
+    // [left, right) 是目前向右延伸最远的与前缀相同的区间
+    let (mut left, mut right) = (0, 0);
+    for i in 1..s.len() {
+        if i < right {
+            z[i] = z[i - left].min(right - i);
+        }
+        while i + z[i] < s.len() && s[z[i]] == s[i + z[i]] {
+            z[i] += 1;
+        }
+        if i + z[i] > right {
+            left = i;
+            right = i + z[i];
+        }
+    }
+    z
+}
+
+/// Same as `kmp_search`, but also return the failure table used.
+pub fn kmp_search_debug(text: &str, pattern: &str) -> (Option<usize>, Vec<usize>) {
+    let pattern = KmpPattern::new(pattern);
+    (pattern.find(text), pattern.next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        kmp_find_n, kmp_for_each_match, kmp_search, kmp_search_byte_offset, kmp_search_circular, kmp_search_debug,
+        overlap_len, search_one_mismatch, KmpPattern, PatternStats,
+    };
+
+    #[test]
+    fn test_kmp_search() {
+        assert_eq!(kmp_search("", ""), Some(0));
+        assert_eq!(kmp_search("abc", ""), Some(0));
+        assert_eq!(kmp_search("", "a"), None);
+        assert_eq!(kmp_search("ababcabcacbab", "abcac"), Some(5));
+        assert_eq!(kmp_search("aaaab", "aab"), Some(2));
+        assert_eq!(kmp_search("abcabd", "abd"), Some(3));
+        assert_eq!(kmp_search("abcabc", "abd"), None);
+        assert_eq!(kmp_search("你好世界", "世界"), Some(2));
+    }
+
+    #[test]
+    fn test_kmp_search_byte_offset() {
+        assert_eq!(kmp_search_byte_offset("", ""), Some(0));
+        assert_eq!(kmp_search_byte_offset("abc", "bc"), Some(1));
+        assert_eq!(kmp_search_byte_offset("abc", "bd"), None);
+
+        // 匹配之前有多字节字符，字节偏移与 char 下标不同
+        let text = "你好🦀, world";
+        assert_eq!(kmp_search(text, "world"), Some(5));
+        let offset = kmp_search_byte_offset(text, "world").unwrap();
+        assert_eq!(offset, 12);
+        assert_eq!(&text[offset..], "world");
+
+        // 模式串本身也是多字节字符
+        let offset = kmp_search_byte_offset(text, "🦀").unwrap();
+        assert_eq!(offset, 6);
+        assert_eq!(&text[offset..offset + "🦀".len()], "🦀");
+    }
+
+    #[test]
+    fn test_kmp_search_circular() {
+        // 从末尾绕回开头的匹配
+        assert_eq!(kmp_search_circular("cdeab", "abcd"), Some(3));
+        assert_eq!(kmp_search_circular("cdeab", "bc"), Some(4));
+        // 不需要绕回时与 kmp_search 相同
+        assert_eq!(kmp_search_circular("cdeab", "dea"), Some(1));
+        assert_eq!(kmp_search_circular("cdeab", "cdeab"), Some(0));
+        assert_eq!(kmp_search_circular("cdeab", "abcde"), Some(3));
+        assert_eq!(kmp_search_circular("cdeab", "ac"), None);
+        // 比 text 长的模式串不会匹配
+        assert_eq!(kmp_search_circular("ab", "aba"), None);
+        assert_eq!(kmp_search_circular("", "a"), None);
+        assert_eq!(kmp_search_circular("abc", ""), Some(0));
+        assert_eq!(kmp_search_circular("界你好世", "世界"), Some(3));
+    }
+
+    #[test]
+    fn test_overlap_len() {
+        assert_eq!(overlap_len("abcde", "cdefg"), 3);
+        assert_eq!(overlap_len("abc", "xyz"), 0);
+        assert_eq!(overlap_len("abc", "abc"), 3);
+        // b 在 a 中间完整出现过，但结尾只重叠了一部分
+        assert_eq!(overlap_len("abcab", "abc"), 2);
+        assert_eq!(overlap_len("aaaa", "aa"), 2);
+        assert_eq!(overlap_len("ab", "abcd"), 2);
+        assert_eq!(overlap_len("abx", "abcd"), 0);
+        assert_eq!(overlap_len("xab", "abcd"), 2);
+        assert_eq!(overlap_len("", "abc"), 0);
+        assert_eq!(overlap_len("abc", ""), 0);
+        assert_eq!(overlap_len("你好世界", "世界和平"), 2);
+    }
+
+    #[test]
+    fn test_kmp_search_debug() {
+        assert_eq!(kmp_search_debug("ababcabcacbab", "abcac"), (Some(5), vec![0, 0, 0, 1, 0]));
+        assert_eq!(kmp_search_debug("abcabc", "abd"), (None, vec![0, 0, 0]));
+        assert_eq!(kmp_search_debug("abc", ""), (Some(0), vec![]));
+    }
+
+    #[test]
+    fn test_find_iter() {
+        let pattern = KmpPattern::new("aa");
+        assert_eq!(pattern.find_iter("aaaa").collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(pattern.find_iter("abaab").collect::<Vec<_>>(), vec![2]);
+        assert_eq!(pattern.find_iter("").count(), 0);
+
+        let pattern = KmpPattern::new("aba");
+        assert_eq!(pattern.find_iter("ababa, aba").collect::<Vec<_>>(), vec![0, 2, 7]);
+
+        // 空模式串在每个 char 下标都匹配
+        let pattern = KmpPattern::new("");
+        assert_eq!(pattern.find_iter("你好").collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(pattern.find_iter("").collect::<Vec<_>>(), vec![0]);
+    }
+
+    #[test]
+    fn test_kmp_find_n() {
+        assert_eq!(kmp_find_n("aaaaaa", "aa", 2), vec![0, 1]);
+        assert_eq!(kmp_find_n("aaaaaa", "aa", 10), vec![0, 1, 2, 3, 4]);
+        assert_eq!(kmp_find_n("aaaaaa", "aa", 0), vec![]);
+        assert_eq!(kmp_find_n("abcabc", "x", 3), vec![]);
+        assert_eq!(kmp_find_n("你好你好", "你", 1), vec![0]);
+    }
+
+    #[test]
+    fn test_kmp_for_each_match() {
+        let text = "abcabcabc 你好abc";
+        let mut offsets = Vec::new();
+        kmp_for_each_match(text, "abc", |offset| offsets.push(offset));
+        assert_eq!(offsets, vec![0, 3, 6, 12]);
+        assert_eq!(offsets, KmpPattern::new("abc").find_iter(text).collect::<Vec<_>>());
+
+        let mut count = 0;
+        kmp_for_each_match("aaaaa", "aaa", |_| count += 1);
+        assert_eq!(count, 3);
+
+        kmp_for_each_match("abc", "d", |_| panic!("no match expected"));
+    }
+
+    #[test]
+    fn test_next() {
+        assert_eq!(KmpPattern::new("abcac").next(), &[0, 0, 0, 1, 0]);
+        assert_eq!(KmpPattern::new("aabaaab").next(), &[0, 1, 0, 1, 2, 2, 3]);
+    }
+
+    #[test]
+    fn test_stats() {
+        assert_eq!(
+            KmpPattern::new("aaaa").stats(),
+            PatternStats { max_border: 3, total_border: 6 }
+        );
+        assert_eq!(
+            KmpPattern::new("abcd").stats(),
+            PatternStats { max_border: 0, total_border: 0 }
+        );
+        assert_eq!(
+            KmpPattern::new("").stats(),
+            PatternStats { max_border: 0, total_border: 0 }
+        );
+    }
+
+    #[test]
+    fn test_search_one_mismatch() {
+        assert_eq!(search_one_mismatch(b"abcde", b"abXde"), Some(0));
+        assert_eq!(search_one_mismatch(b"abcde", b"aXYde"), None);
+        // 完全相同也算
+        assert_eq!(search_one_mismatch(b"abcde", b"cde"), Some(2));
+        // 不同的字节在开头或末尾
+        assert_eq!(search_one_mismatch(b"xxabc", b"Xbc"), Some(2));
+        assert_eq!(search_one_mismatch(b"abcxx", b"abX"), Some(0));
+        // 返回第一个位置，即使后面有完全匹配
+        assert_eq!(search_one_mismatch(b"abdabc", b"abc"), Some(0));
+        assert_eq!(search_one_mismatch(b"aaaa", b"aaab"), Some(0));
+        assert_eq!(search_one_mismatch(b"ab", b"abc"), None);
+        assert_eq!(search_one_mismatch(b"abc", b""), Some(0));
+        assert_eq!(search_one_mismatch(b"a", b"b"), Some(0));
+        assert_eq!(search_one_mismatch(b"babab", b"aXa"), Some(1));
+    }
+
+    #[test]
+    fn test_search_one_mismatch_brute_force() {
+        // 与逐个窗口数不同字节的结果比较
+        let texts: [&[u8]; 4] = [b"abababcab", b"aabbaabb", b"cccc", b"abcabcabd"];
+        let patterns: [&[u8]; 6] = [b"abc", b"aab", b"bab", b"cbd", b"abcd", b"ccca"];
+        for text in texts {
+            for pattern in patterns {
+                let expected = (0..=text.len() - pattern.len()).find(|&i| {
+                    text[i..i + pattern.len()].iter().zip(pattern).filter(|(a, b)| a != b).count() <= 1
+                });
+                assert_eq!(search_one_mismatch(text, pattern), expected, "{:?} {:?}", text, pattern);
+            }
+        }
+    }
+}
@@ -1,8 +1,136 @@
+use std::{collections::VecDeque, ops::Range};
+use crate::{prng::Xoshiro256StarStar, ringbuffer::RingBuffer, rng::{gen_range, Rng}};
 
+// knuth_shuffle_batched 每次从生成器中取出的 u64 个数
+const BATCH_LEN: usize = 16;
+
+/// Shuffle with the default generator.
+/// It is the thread-local built-in PRNG, or `rand::thread_rng` with the `rand` feature.
 pub fn knuth_shuffle<T>(input: &mut [T]) {
-    for i in (0..input.len()).rev() {
+    #[cfg(feature = "rand")]
+    shuffle_with(input, &mut rand::thread_rng());
+    #[cfg(not(feature = "rand"))]
+    crate::prng::with_thread_rng(|rng| shuffle_with(input, rng));
+}
+
+/// Shuffle with the given generator.
+pub fn shuffle_with<T, R: Rng>(input: &mut [T], rng: &mut R) {
+    for i in (1..input.len()).rev() {
+        // Swap elements
+        input.swap(i, rng.gen_range(i + 1));
+    }
+}
+
+/// Same as `shuffle_with`, but draw the random numbers from `rng` in batches.
+/// Every `u64` gives two 32-bit indices while the length fits in `u32`, so `rng` is called about half as often.
+/// The permutations are still uniform, but differ from `shuffle_with` for the same seed.
+pub fn knuth_shuffle_batched<T, R: Rng>(input: &mut [T], rng: &mut R) {
+    let mut batch = Batch { rng, buf: [0; BATCH_LEN], pos: BATCH_LEN * 2 };
+    for i in (1..input.len()).rev() {
+        input.swap(i, batch.gen_index(i + 1));
+    }
+}
+
+/// Random numbers taken from the wrapped generator `BATCH_LEN` at a time.
+struct Batch<'a, R: Rng> {
+    rng: &'a mut R,
+    buf: [u64; BATCH_LEN],
+    // 下一个要用的 32 位的位置，每个 u64 分成两个 32 位
+    pos: usize,
+}
+
+impl<R: Rng> Batch<'_, R> {
+    fn next_u32(&mut self) -> u32 {
+        if self.pos == BATCH_LEN * 2 {
+            for x in self.buf.iter_mut() {
+                *x = self.rng.next_u64();
+            }
+            self.pos = 0;
+        }
+        let x = self.buf[self.pos / 2] >> (self.pos % 2 * 32);
+        self.pos += 1;
+        x as u32
+    }
+
+    /// Return a uniformly random index in `[0, bound)`.
+    fn gen_index(&mut self, bound: usize) -> usize {
+        if bound > u32::MAX as usize {
+            return gen_range(self, bound);
+        }
+
+        // 与 `rng::gen_range` 相同的 Lemire 方法，只是换成 32 位
+        let bound = bound as u32;
+        let mut m = self.next_u32() as u64 * bound as u64;
+        let mut low = m as u32;
+        if low < bound {
+            let threshold = bound.wrapping_neg() % bound;
+            while low < threshold {
+                m = self.next_u32() as u64 * bound as u64;
+                low = m as u32;
+            }
+        }
+        (m >> 32) as usize
+    }
+}
+
+impl<R: Rng> Rng for Batch<'_, R> {
+    fn next_u64(&mut self) -> u64 {
+        self.next_u32() as u64 | (self.next_u32() as u64) << 32
+    }
+}
+
+/// Shuffle with the built-in PRNG seeded by `seed`.
+/// The same seed always gives the same permutation.
+pub fn shuffle_seeded<T>(input: &mut [T], seed: u64) {
+    shuffle_with(input, &mut Xoshiro256StarStar::seed_from_u64(seed));
+}
+
+/// Shuffle only `input[range]`, the rest of the slice is left untouched.
+///
+/// Panics if the range is out of bounds, the same as slicing.
+pub fn shuffle_range<T, R: Rng>(input: &mut [T], range: Range<usize>, rng: &mut R) {
+    shuffle_with(&mut input[range], rng);
+}
+
+/// Shuffle the elements that are not at the `pinned` positions among themselves,
+/// the pinned positions are left untouched.
+///
+/// Panics if a pinned position is out of bounds or repeated.
+pub fn shuffle_with_pins<T, R: Rng>(input: &mut [T], pinned: &[usize], rng: &mut R) {
+    let len = input.len();
+    let mut is_pinned = vec![false; len];
+    for &index in pinned {
+        assert!(index < len, "pinned index out of bounds: the len is {} but the index is {}", len, index);
+        assert!(!is_pinned[index], "pinned index {} is repeated", index);
+        is_pinned[index] = true;
+    }
+
+    // 只在未固定的位置上做 Fisher-Yates
+    let free: Vec<usize> = (0..len).filter(|&index| !is_pinned[index]).collect();
+    for i in (1..free.len()).rev() {
+        input.swap(free[i], free[rng.gen_range(i + 1)]);
+    }
+}
+
+/// Collect the iterator into a `Vec` and shuffle it.
+pub fn shuffled<T, I: IntoIterator<Item = T>, R: Rng>(iter: I, rng: &mut R) -> Vec<T> {
+    let mut result: Vec<T> = iter.into_iter().collect();
+    shuffle_with(&mut result, rng);
+    result
+}
+
+/// Shuffle the elements of a `VecDeque` in place.
+/// It calls `make_contiguous` first, so the internal representation may be rotated.
+pub fn shuffle_deque<T, R: Rng>(deque: &mut VecDeque<T>, rng: &mut R) {
+    shuffle_with(deque.make_contiguous(), rng);
+}
+
+/// Shuffle the live elements of a `RingBuffer` in place.
+/// 按照从 head 开始的逻辑偏移做 Fisher-Yates，不会移动 head 和 tail
+pub fn shuffle_ring<T, R: Rng>(buf: &mut RingBuffer<T>, rng: &mut R) {
+    for i in (1..buf.len()).rev() {
         // Swap elements
-        input.swap(i, rand::random::<usize>() % (i + 1));
+        buf.swap(i, rng.gen_range(i + 1));
     }
 }
 
@@ -10,12 +138,222 @@ pub fn knuth_shuffle<T>(input: &mut [T]) {
 
 #[cfg(test)]
 mod tests {
-    use crate::knuth_shuffle::knuth_shuffle;
+    use std::collections::VecDeque;
+    use crate::knuth_shuffle::{
+        knuth_shuffle, knuth_shuffle_batched, shuffle_deque, shuffle_range, shuffle_ring, shuffle_seeded, shuffle_with,
+        shuffle_with_pins, shuffled,
+    };
+    use crate::prng::Xoshiro256StarStar;
+    use crate::ringbuffer::RingBuffer;
+    use crate::rng::Rng;
 
     #[test]
     fn test_knuth_shuffle() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for len in 0..50 {
+            let mut input: Vec<u32> = (0..len).map(|_| rng.gen_range(10) as u32).collect();
+            let mut expected = input.clone();
+            knuth_shuffle(&mut input);
+            // 打乱前后元素的多重集合不变
+            input.sort();
+            expected.sort();
+            assert_eq!(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_shuffle_seeded() {
+        // 固定种子得到固定的排列，算法或 PRNG 改动时这里会失败
+        let mut input = [1, 2, 3, 4, 5, 6, 7, 8];
+        shuffle_seeded(&mut input, 42);
+        assert_eq!(input, [8, 2, 7, 4, 6, 5, 3, 1]);
+
+        let mut input = [1, 2, 3, 4, 5, 6, 7, 8];
+        shuffle_seeded(&mut input, 42);
+        assert_eq!(input, [8, 2, 7, 4, 6, 5, 3, 1]);
+    }
+
+    #[test]
+    fn test_shuffle_uniform() {
+        // 每个元素出现在每个位置的概率都应为 1/4
+        // 期望 4000 次，只作宽松的检查
+        let mut counts = [[0; 4]; 4];
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for _ in 0..16000 {
+            let mut input = [0, 1, 2, 3];
+            shuffle_with(&mut input, &mut rng);
+            for (position, &value) in input.iter().enumerate() {
+                counts[value][position] += 1;
+            }
+        }
+        for row in counts {
+            for count in row {
+                assert!((3500..4500).contains(&count), "{:?}", counts);
+            }
+        }
+    }
+
+    #[test]
+    fn test_knuth_shuffle_batched() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for len in (0..50).chain([1000]) {
+            let mut input: Vec<u32> = (0..len).collect();
+            knuth_shuffle_batched(&mut input, &mut rng);
+            input.sort();
+            assert_eq!(input, (0..len).collect::<Vec<u32>>());
+        }
+
+        // 与 test_shuffle_uniform 相同的宽松检查
+        let mut counts = [[0; 4]; 4];
+        for _ in 0..16000 {
+            let mut input = [0, 1, 2, 3];
+            knuth_shuffle_batched(&mut input, &mut rng);
+            for (position, &value) in input.iter().enumerate() {
+                counts[value][position] += 1;
+            }
+        }
+        for row in counts {
+            for count in row {
+                assert!((3500..4500).contains(&count), "{:?}", counts);
+            }
+        }
+    }
+
+    #[test]
+    fn test_shuffle_with() {
+        let mut input = [1, 2, 3, 4, 5];
+        let mut output = [1, 2, 3, 4, 5];
+        shuffle_with(&mut input, &mut Xoshiro256StarStar::seed_from_u64(1));
+        shuffle_with(&mut output, &mut Xoshiro256StarStar::seed_from_u64(1));
+        // 相同的种子得到相同的排列
+        assert_eq!(input, output);
+
+        input.sort();
+        assert_eq!(input, [1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_shuffle_range() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for _ in 0..20 {
+            let mut input = [0, 1, 2, 3, 4, 5, 6];
+            shuffle_range(&mut input, 2..5, &mut rng);
+            assert_eq!(input[..2], [0, 1]);
+            assert_eq!(input[5..], [5, 6]);
+
+            let mut middle = input[2..5].to_vec();
+            middle.sort();
+            assert_eq!(middle, [2, 3, 4]);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_shuffle_range_out_of_bounds() {
+        let mut input = [0, 1, 2];
+        shuffle_range(&mut input, 1..4, &mut Xoshiro256StarStar::seed_from_u64(1));
+    }
+
+    #[test]
+    fn test_shuffle_with_pins() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        let mut seen = Vec::new();
+        for _ in 0..50 {
+            let mut input = ['a', 'b', 'c', 'd', 'e'];
+            shuffle_with_pins(&mut input, &[0, 4], &mut rng);
+            assert_eq!(input[0], 'a');
+            assert_eq!(input[4], 'e');
+
+            let mut middle = input[1..4].to_vec();
+            if !seen.contains(&middle) {
+                seen.push(middle.clone());
+            }
+            middle.sort();
+            assert_eq!(middle, ['b', 'c', 'd']);
+        }
+        // 其余 3 个元素的 6 种排列都会出现
+        assert_eq!(seen.len(), 6);
+
+        // 全部固定时不发生变化
+        let mut input = [1, 2, 3];
+        shuffle_with_pins(&mut input, &[2, 0, 1], &mut rng);
+        assert_eq!(input, [1, 2, 3]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_shuffle_with_pins_out_of_bounds() {
+        let mut input = [1, 2, 3];
+        shuffle_with_pins(&mut input, &[3], &mut Xoshiro256StarStar::seed_from_u64(1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_shuffle_with_pins_repeated() {
+        let mut input = [1, 2, 3];
+        shuffle_with_pins(&mut input, &[1, 1], &mut Xoshiro256StarStar::seed_from_u64(1));
+    }
+
+    #[test]
+    fn test_shuffled() {
+        for seed in 0..10 {
+            let mut input: Vec<i32> = (0..10).collect();
+            shuffle_with(&mut input, &mut Xoshiro256StarStar::seed_from_u64(seed));
+            // 相同种子下与原地打乱的结果一致，所以分布也一致
+            assert_eq!(shuffled(0..10, &mut Xoshiro256StarStar::seed_from_u64(seed)), input);
+        }
+        assert_eq!(shuffled(Vec::<i32>::new(), &mut Xoshiro256StarStar::seed_from_u64(0)), vec![]);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_shuffle_with_rand() {
+        use rand::{rngs::StdRng, SeedableRng};
+
         let mut input = [1, 2, 3, 4, 5];
-        knuth_shuffle(&mut input);
-        println!("{:?}", input);
+        shuffle_with(&mut input, &mut StdRng::seed_from_u64(1));
+        input.sort();
+        assert_eq!(input, [1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_shuffle_deque() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        let mut deque = VecDeque::new();
+        for i in 0..5 {
+            deque.push_back(i);
+            deque.push_front(-i - 1);
+        }
+
+        shuffle_deque(&mut deque, &mut rng);
+        let mut output: Vec<i32> = deque.into_iter().collect();
+        output.sort();
+        assert_eq!(output, (-5..5).collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn test_shuffle_ring() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(7);
+        let mut buf = RingBuffer::with_capacity(8);
+        // 先推入再弹出，使内容跨越缓冲区末尾
+        for i in 0..5 {
+            buf.push(i);
+        }
+        for _ in 0..5 {
+            buf.pop();
+        }
+        for i in 0..7 {
+            buf.push(i);
+        }
+
+        shuffle_ring(&mut buf, &mut rng);
+        assert_eq!(buf.len(), 7);
+
+        let mut output = Vec::new();
+        while let Some(value) = buf.pop() {
+            output.push(value);
+        }
+        output.sort();
+        assert_eq!(output, vec![0, 1, 2, 3, 4, 5, 6]);
     }
 }
@@ -0,0 +1,68 @@
+//! K-th largest element of a stream
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+/// Return the k-th largest item (1-based) of the iterator, or `None` if there are fewer than `k` items.
+/// It keeps a min-heap of the `k` largest items seen so far, so O(n log k) time and O(k) space.
+pub fn kth_largest<T: Ord + Clone>(iter: impl Iterator<Item = T>, k: usize) -> Option<T> {
+    if k == 0 {
+        return None;
+    }
+
+    // 堆顶是目前最大的 k 个值中最小的一个
+    // k 可能远大于元素个数，预分配不超过已知的元素个数下限
+    let mut heap = BinaryHeap::with_capacity(k.min(iter.size_hint().0));
+    for item in iter {
+        if heap.len() < k {
+            heap.push(Reverse(item));
+        } else if let Some(mut top) = heap.peek_mut() {
+            // 比堆顶大时替换堆顶，PeekMut 在释放时会重新调整堆
+            if item > top.0 {
+                *top = Reverse(item);
+            }
+        }
+    }
+
+    if heap.len() < k {
+        return None;
+    }
+    heap.peek().map(|top| top.0.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{prng::Xoshiro256StarStar, rng::Rng};
+    use super::kth_largest;
+
+    #[test]
+    fn test_kth_largest() {
+        assert_eq!(kth_largest(vec![3, 1, 4, 1, 5, 9, 2, 6].into_iter(), 1), Some(9));
+        assert_eq!(kth_largest(vec![3, 1, 4, 1, 5, 9, 2, 6].into_iter(), 3), Some(5));
+        assert_eq!(kth_largest(vec![3, 1, 4, 1, 5, 9, 2, 6].into_iter(), 8), Some(1));
+        // 重复值分别计数
+        assert_eq!(kth_largest(vec![7, 7, 7, 1].into_iter(), 3), Some(7));
+    }
+
+    #[test]
+    fn test_kth_largest_not_enough() {
+        assert_eq!(kth_largest(vec![1, 2, 3].into_iter(), 4), None);
+        assert_eq!(kth_largest(std::iter::empty::<i32>(), 1), None);
+        assert_eq!(kth_largest(vec![1, 2, 3].into_iter(), 0), None);
+        // 不会按 k 预分配
+        assert_eq!(kth_largest(vec![1, 2, 3].into_iter(), usize::MAX), None);
+        assert_eq!(kth_largest(0..1000, usize::MAX), None);
+    }
+
+    #[test]
+    fn test_kth_largest_random() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for len in 1..60 {
+            let input: Vec<u32> = (0..len).map(|_| rng.gen_range(20) as u32).collect();
+            let mut sorted = input.clone();
+            sorted.sort_by(|a, b| b.cmp(a));
+            for k in 1..=len {
+                assert_eq!(kth_largest(input.iter().copied(), k), Some(sorted[k - 1]));
+            }
+        }
+    }
+}
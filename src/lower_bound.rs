@@ -0,0 +1,203 @@
+//! Lower bound and upper bound of sorted slices
+
+/// Return the first index where `pred` is false.
+/// The slice must be partitioned, i.e. `pred` is true for a prefix and false for the rest.
+pub fn partition_point<T, P: FnMut(&T) -> bool>(input: &[T], mut pred: P) -> usize {
+    // 搜索区域是 [low, high)
+    let mut low: usize = 0;
+    let mut high = input.len();
+
+    while low < high {
+        // `low + (high - low) / 2` 就和 `(low + high) / 2` 的结果相同
+        // 但是有效防止了 low 和 high 太大直接相加导致溢出
+        let middle = low + (high - low) / 2;
+        if pred(&input[middle]) {
+            // 搜索区域右移 [middle+1, high)
+            low = middle + 1;
+        } else {
+            // 搜索区域左移 [low, middle)，middle 可能就是结果
+            high = middle;
+        }
+    }
+
+    // 此时 low == high
+    low
+}
+
+/// Return the first index whose value is not less than `key`.
+/// It is also the number of elements less than `key`.
+pub fn lower_bound<T: Ord>(input: &[T], key: &T) -> usize {
+    partition_point(input, |x| x < key)
+}
+
+/// Return the first index whose value is greater than `key`.
+/// It is also the number of elements not greater than `key`.
+pub fn upper_bound<T: Ord>(input: &[T], key: &T) -> usize {
+    partition_point(input, |x| x <= key)
+}
+
+/// Return the rank of `key`, i.e. the number of elements strictly less than `key`.
+/// It is the same as `lower_bound`, named for order statistics.
+pub fn rank_of<T: Ord>(input: &[T], key: &T) -> usize {
+    lower_bound(input, key)
+}
+
+/// Iterate over the indices of all elements equal to `key`, i.e. `lower_bound..upper_bound`.
+/// Both bounds are found before the first index is yielded.
+pub fn matching_indices<T: Ord>(input: &[T], key: T) -> impl Iterator<Item = usize> {
+    let low = lower_bound(input, &key);
+    // 上界一定不在 low 之前，只需要在剩余部分中查找
+    let high = low + upper_bound(&input[low..], &key);
+    low..high
+}
+
+/// Return the index of the `k`-th (1-based) element equal to `key`, `None` if there are fewer than `k`.
+/// `k == 0` is always `None`.
+pub fn kth_occurrence<T: Ord>(input: &[T], key: T, k: usize) -> Option<usize> {
+    let index = lower_bound(input, &key).checked_add(k.checked_sub(1)?)?;
+    // 有序时相等的元素连续，只需检查这个位置是否还等于 key
+    match input.get(index) {
+        Some(value) if *value == key => Some(index),
+        _ => None,
+    }
+}
+
+/// Return the strings in `sorted` that start with `prefix`, the slice must be sorted.
+/// The end is the first string not less than `prefix` with its last char incremented,
+/// trailing `char::MAX` are dropped first since they can't be incremented, an empty prefix matches all.
+pub fn prefix_range<'a>(sorted: &'a [String], prefix: &str) -> &'a [String] {
+    let low = partition_point(sorted, |s| s.as_str() < prefix);
+    let mut end = prefix.to_string();
+    // 以 prefix 开头的字符串都小于 end，不以它开头但不小于 prefix 的都不小于 end
+    let high = loop {
+        match end.pop() {
+            Some(last) => {
+                if let Some(next) = next_char(last) {
+                    end.push(next);
+                    break low + partition_point(&sorted[low..], |s| s.as_str() < end.as_str());
+                }
+            }
+            // 没有能递增的字符，之后的字符串都以 prefix 开头
+            None => break sorted.len(),
+        }
+    };
+    &sorted[low..high]
+}
+
+/// The char after `c` in code point order, skipping the surrogates that are not chars.
+fn next_char(c: char) -> Option<char> {
+    match c {
+        char::MAX => None,
+        '\u{D7FF}' => Some('\u{E000}'),
+        _ => char::from_u32(c as u32 + 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{kth_occurrence, lower_bound, matching_indices, partition_point, prefix_range, rank_of, upper_bound};
+
+    #[test]
+    fn test_partition_point() {
+        let input = [1.0, 2.5, 2.5, 4.0];
+        assert_eq!(partition_point(&input, |&x| x < 2.5), 1);
+        assert_eq!(partition_point(&input, |&x| x <= 2.5), 3);
+        assert_eq!(partition_point(&input, |_| true), 4);
+        assert_eq!(partition_point(&input, |_| false), 0);
+        assert_eq!(partition_point(&[] as &[f64], |_| true), 0);
+    }
+
+    #[test]
+    fn test_lower_bound() {
+        let input = [1, 2, 2, 2, 3, 5];
+        assert_eq!(lower_bound(&input, &0), 0);
+        assert_eq!(lower_bound(&input, &1), 0);
+        assert_eq!(lower_bound(&input, &2), 1);
+        assert_eq!(lower_bound(&input, &3), 4);
+        assert_eq!(lower_bound(&input, &4), 5);
+        assert_eq!(lower_bound(&input, &9), 6);
+        assert_eq!(lower_bound(&[] as &[i32], &1), 0);
+    }
+
+    #[test]
+    fn test_upper_bound() {
+        let input = [1, 2, 2, 2, 3, 5];
+        assert_eq!(upper_bound(&input, &0), 0);
+        assert_eq!(upper_bound(&input, &1), 1);
+        assert_eq!(upper_bound(&input, &2), 4);
+        assert_eq!(upper_bound(&input, &4), 5);
+        assert_eq!(upper_bound(&input, &5), 6);
+        assert_eq!(upper_bound(&[] as &[i32], &1), 0);
+    }
+
+    #[test]
+    fn test_rank_of() {
+        let input = [1, 2, 2, 3, 5];
+        assert_eq!(rank_of(&input, &2), 1);
+        assert_eq!(rank_of(&input, &4), 4);
+        assert_eq!(rank_of(&input, &0), 0);
+        assert_eq!(rank_of(&input, &9), 5);
+        assert_eq!(rank_of(&[] as &[i32], &1), 0);
+    }
+
+    #[test]
+    fn test_matching_indices() {
+        let input = [1, 2, 2, 2, 3];
+        assert_eq!(matching_indices(&input, 2).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(matching_indices(&input, 1).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(matching_indices(&input, 3).collect::<Vec<_>>(), vec![4]);
+        assert_eq!(matching_indices(&input, 9).count(), 0);
+        assert_eq!(matching_indices(&input, 0).count(), 0);
+        assert_eq!(matching_indices(&[] as &[i32], 0).count(), 0);
+        assert_eq!(matching_indices(&[5; 4], 5).collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_kth_occurrence() {
+        assert_eq!(kth_occurrence(&[2, 2, 2], 2, 1), Some(0));
+        assert_eq!(kth_occurrence(&[2, 2, 2], 2, 3), Some(2));
+        assert_eq!(kth_occurrence(&[2, 2, 2], 2, 4), None);
+        assert_eq!(kth_occurrence(&[2, 2, 2], 2, 0), None);
+        assert_eq!(kth_occurrence(&[2, 2, 2], 2, usize::MAX), None);
+
+        let input = [1, 3, 3, 5, 5, 5, 7];
+        assert_eq!(kth_occurrence(&input, 5, 2), Some(4));
+        // 超出相等的部分，即使下标还在范围内
+        assert_eq!(kth_occurrence(&input, 3, 3), None);
+        assert_eq!(kth_occurrence(&input, 4, 1), None);
+        assert_eq!(kth_occurrence(&[] as &[i32], 1, 1), None);
+    }
+
+    #[test]
+    fn test_prefix_range() {
+        let words: Vec<String> = ["apple", "apply", "banana"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(prefix_range(&words, "app"), &words[0..2]);
+        assert_eq!(prefix_range(&words, "apple"), &words[0..1]);
+        assert_eq!(prefix_range(&words, "b"), &words[2..3]);
+        assert_eq!(prefix_range(&words, ""), &words[..]);
+        assert!(prefix_range(&words, "apq").is_empty());
+        assert!(prefix_range(&words, "c").is_empty());
+        assert!(prefix_range(&[], "a").is_empty());
+    }
+
+    #[test]
+    fn test_prefix_range_char_max() {
+        let max = char::MAX.to_string();
+        let mut words: Vec<String> = vec![
+            "a".to_string(),
+            format!("a{}", max),
+            format!("a{}{}", max, max),
+            format!("a{}b", max),
+            "b".to_string(),
+            format!("{}", max),
+            format!("{}a", max),
+        ];
+        words.sort();
+        assert_eq!(prefix_range(&words, &format!("a{}", max)), &words[1..4]);
+        // 全部是 char::MAX 时没有上界
+        assert_eq!(prefix_range(&words, &max), &words[5..]);
+        // 跳过代理区
+        let words = vec!["\u{D7FF}".to_string(), "\u{D7FF}a".to_string(), "\u{E000}".to_string()];
+        assert_eq!(prefix_range(&words, "\u{D7FF}"), &words[0..2]);
+    }
+}
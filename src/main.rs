@@ -1,19 +1,55 @@
+#![cfg_attr(feature = "simd", feature(portable_simd))]
+
+pub mod error;
+pub mod is_sorted;
 pub mod binary_search;
 pub mod binary_search_left;
 pub mod binary_search_right;
+pub mod binary_search_const;
+pub mod binary_search_by;
+pub mod binary_search_simd;
+pub mod sorted_indexed;
+pub mod lower_bound;
+pub mod sorted_insert;
 pub mod ringbuffer;
+pub mod channel;
+pub mod spsc;
+pub mod work_stealing;
+pub mod bounded_collector;
 pub mod atomic;
+pub mod treiber_stack;
 pub mod knuth_shuffle;
+pub mod rng;
+pub mod prng;
+#[cfg(feature = "rayon")]
+pub mod par_shuffle;
+pub mod choose;
+pub mod bucketing;
+pub mod derangement;
+pub mod kmp;
+pub mod sort_by_cached_key;
+pub mod sorted_intersection;
+pub mod dedup_sorted;
+pub mod select_nth;
+pub mod kth_largest;
+pub mod insertion_sort;
+pub mod quicksort;
+pub mod introsort;
+pub mod counting_sort;
+pub mod radix_sort;
+pub mod partition;
+pub mod merge_sort;
+pub mod heap_sort;
+pub mod binary_heap;
+pub mod tim_sort;
+#[cfg(test)]
+pub mod sort_test_util;
+pub mod sorted_vec_map;
+pub mod tree;
 
-use std::{alloc::{alloc, Layout}, ptr, cell::RefCell, thread};
+use std::{alloc::{alloc, Layout}, ptr, thread};
 use ringbuffer::RingBuffer;
 
-struct TreeNode<T>{  
-	value: Option<T>,  
-	children: Vec<RefCell<TreeNode<T>>>,  
-	parent: Option<Box<RefCell<TreeNode<T>>>>,
-}
-
 unsafe fn get_val() -> *const i32 {
     let mut my_num: Box<i32> = Box::new(10);
     &*my_num
@@ -0,0 +1,217 @@
+//! Merge sort algorithm
+
+use std::cmp::Reverse;
+use crate::binary_heap::BinaryHeap;
+
+/// Merge two sorted slices into `output`.
+/// Stable: on equal elements, the ones from `left` come first.
+///
+/// Panics if `output.len() != left.len() + right.len()`.
+pub fn merge<T: Ord + Clone>(left: &[T], right: &[T], output: &mut [T]) {
+    assert_eq!(output.len(), left.len() + right.len(), "Output length must be the sum of the inputs");
+
+    let mut i = 0;
+    let mut j = 0;
+    for slot in output.iter_mut() {
+        // 右侧严格更小时才取右侧，相等时取左侧，以保持稳定
+        if j == right.len() || (i < left.len() && left[i] <= right[j]) {
+            slot.clone_from(&left[i]);
+            i += 1;
+        } else {
+            slot.clone_from(&right[j]);
+            j += 1;
+        }
+    }
+}
+
+/// Merge two sorted slices into a new `Vec`.
+/// Stable: on equal elements, the ones from `a` come first.
+pub fn merge_sorted<T: Ord + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    let mut result = Vec::with_capacity(a.len() + b.len());
+    let mut i = 0;
+    let mut j = 0;
+    while i < a.len() && j < b.len() {
+        // 相等时取 a 中的元素
+        if a[i] <= b[j] {
+            result.push(a[i].clone());
+            i += 1;
+        } else {
+            result.push(b[j].clone());
+            j += 1;
+        }
+    }
+    // 最多只有一侧还有剩余
+    result.extend_from_slice(&a[i..]);
+    result.extend_from_slice(&b[j..]);
+    result
+}
+
+/// Merge any number of sorted lists into a new `Vec`, in O(n log k) for k lists.
+/// Stable: on equal elements, the ones from earlier lists come first.
+pub fn merge_k_sorted<T: Ord + Clone>(lists: &[Vec<T>]) -> Vec<T> {
+    let mut result = Vec::with_capacity(lists.iter().map(Vec::len).sum());
+    // 堆中每个列表最多一个元素：(当前元素, 列表下标, 元素下标)
+    // BinaryHeap 是大顶堆，用 Reverse 取最小的；元素相等时列表下标小的先出堆
+    let mut heap: BinaryHeap<Reverse<(T, usize, usize)>> = BinaryHeap::from_vec(
+        lists
+            .iter()
+            .enumerate()
+            .filter_map(|(list, items)| items.first().map(|first| Reverse((first.clone(), list, 0))))
+            .collect(),
+    );
+    while let Some(Reverse((value, list, index))) = heap.pop() {
+        result.push(value);
+        if let Some(next) = lists[list].get(index + 1) {
+            heap.push(Reverse((next.clone(), list, index + 1)));
+        }
+    }
+    result
+}
+
+/// Sort the slice with a stable bottom-up merge sort.
+/// O(n log n), the scratch buffer is allocated once.
+pub fn merge_sort<T: Ord + Clone>(input: &mut [T]) {
+    merge_sort_with_scratch(input, &mut Vec::new());
+}
+
+/// Same as `merge_sort`, but merges through the caller's `scratch`, so repeated sorts can reuse one allocation.
+/// `scratch` grows to `input.len()` if it is shorter and is never shrunk, its contents are overwritten.
+pub fn merge_sort_with_scratch<T: Ord + Clone>(input: &mut [T], scratch: &mut Vec<T>) {
+    let len = input.len();
+    if len <= 1 {
+        return;
+    }
+
+    // 只补上缺少的部分，已有的元素之后会被覆盖
+    if scratch.len() < len {
+        let filled = scratch.len();
+        scratch.extend_from_slice(&input[filled..]);
+    }
+    let scratch = &mut scratch[..len];
+    // 数据当前是否在 input 中，每一轮都在 input 和 scratch 之间来回合并
+    let mut in_input = true;
+    // 每一轮把相邻两个长为 width 的有序段合并
+    let mut width = 1;
+    while width < len {
+        let (src, dst): (&[T], &mut [T]) = if in_input {
+            (input, scratch)
+        } else {
+            (scratch, input)
+        };
+        for start in (0..len).step_by(2 * width) {
+            let middle = (start + width).min(len);
+            let end = (start + 2 * width).min(len);
+            merge(&src[start..middle], &src[middle..end], &mut dst[start..end]);
+        }
+
+        in_input = !in_input;
+        width *= 2;
+    }
+
+    if !in_input {
+        input.clone_from_slice(scratch);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{prng::Xoshiro256StarStar, rng::Rng, sort_test_util::{assert_stable, check_sort, Record}};
+    use super::{merge, merge_k_sorted, merge_sort, merge_sort_with_scratch, merge_sorted};
+
+    #[test]
+    fn test_merge() {
+        let mut output = [0; 7];
+        merge(&[1, 3, 5, 7], &[2, 3, 8], &mut output);
+        assert_eq!(output, [1, 2, 3, 3, 5, 7, 8]);
+
+        let mut output = [0; 2];
+        merge(&[], &[1, 2], &mut output);
+        assert_eq!(output, [1, 2]);
+
+        let mut output = [
+            Record { key: 0, tag: 0 },
+            Record { key: 0, tag: 0 },
+            Record { key: 0, tag: 0 },
+        ];
+        merge(
+            &[Record { key: 1, tag: 0 }, Record { key: 2, tag: 1 }],
+            &[Record { key: 1, tag: 2 }],
+            &mut output,
+        );
+        let tags: Vec<u32> = output.iter().map(|r| r.tag).collect();
+        assert_eq!(tags, vec![0, 2, 1]);
+    }
+
+    #[test]
+    fn test_merge_sorted() {
+        assert_eq!(merge_sorted::<i32>(&[], &[]), vec![]);
+        assert_eq!(merge_sorted(&[1, 2], &[]), vec![1, 2]);
+        assert_eq!(merge_sorted(&[], &[1, 2]), vec![1, 2]);
+        assert_eq!(merge_sorted(&[5, 6, 7], &[1, 2]), vec![1, 2, 5, 6, 7]);
+        assert_eq!(merge_sorted(&[1, 2], &[5, 6, 7]), vec![1, 2, 5, 6, 7]);
+        assert_eq!(merge_sorted(&[1, 2, 2, 4], &[2, 3, 4, 4]), vec![1, 2, 2, 2, 3, 4, 4, 4]);
+
+        // 相等时 a 中的元素在前
+        let a = [Record { key: 1, tag: 0 }, Record { key: 2, tag: 1 }];
+        let b = [Record { key: 1, tag: 2 }, Record { key: 2, tag: 3 }];
+        let tags: Vec<u32> = merge_sorted(&a, &b).iter().map(|r| r.tag).collect();
+        assert_eq!(tags, vec![0, 2, 1, 3]);
+    }
+
+    #[test]
+    fn test_merge_k_sorted() {
+        assert_eq!(merge_k_sorted(&[vec![1, 4], vec![2, 5], vec![3, 6]]), vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(merge_k_sorted::<i32>(&[]), vec![]);
+        assert_eq!(merge_k_sorted::<i32>(&[vec![], vec![]]), vec![]);
+        assert_eq!(merge_k_sorted(&[vec![], vec![2, 3], vec![], vec![1, 3, 9]]), vec![1, 2, 3, 3, 9]);
+        assert_eq!(merge_k_sorted(&[vec![7, 8]]), vec![7, 8]);
+
+        // 相等时前面列表中的元素在前
+        let lists = vec![
+            vec![Record { key: 1, tag: 0 }, Record { key: 2, tag: 1 }],
+            vec![Record { key: 1, tag: 2 }],
+            vec![Record { key: 1, tag: 3 }, Record { key: 2, tag: 4 }],
+        ];
+        let tags: Vec<u32> = merge_k_sorted(&lists).iter().map(|r| r.tag).collect();
+        assert_eq!(tags, vec![0, 2, 3, 1, 4]);
+    }
+
+    #[test]
+    fn test_merge_sort() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for len in 0..100 {
+            check_sort::<i32>((0..len).map(|_| rng.gen_range(50) as i32).collect(), merge_sort);
+        }
+    }
+
+    #[test]
+    fn test_merge_sort_stable() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for len in [0, 1, 2, 7, 100, 1000] {
+            let mut input: Vec<Record> = (0..len)
+                .map(|tag| Record { key: rng.gen_range(10) as u32, tag })
+                .collect();
+            merge_sort(&mut input);
+            // key 相同的记录保持输入时 tag 的顺序
+            assert_stable(&input);
+        }
+    }
+
+    #[test]
+    fn test_merge_sort_with_scratch() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        let mut scratch = Vec::new();
+        for len in [5, 20, 0, 3, 20, 1] {
+            let input: Vec<i32> = (0..len).map(|_| rng.gen_range(50) as i32).collect();
+            check_sort(input, |input| merge_sort_with_scratch(input, &mut scratch));
+        }
+        // 只增长到最长的输入，不会缩小
+        assert_eq!(scratch.len(), 20);
+
+        // 比输入长的 scratch 只用到前面一部分
+        let mut input = vec![3, 1, 2];
+        merge_sort_with_scratch(&mut input, &mut scratch);
+        assert_eq!(input, vec![1, 2, 3]);
+        assert_eq!(scratch.len(), 20);
+    }
+}
@@ -0,0 +1,138 @@
+//! Parallel shuffle algorithm
+//!
+//! MergeShuffle (Bacher, Bodini, Hollender and Lumbroso, 2015):
+//! the slice is split in halves recursively, small blocks are shuffled by Fisher-Yates in parallel,
+//! and then the shuffled halves are merged by random interleaving, also in parallel.
+//! Each merge keeps the result a uniformly random permutation,
+//! given that both halves are uniformly random permutations of their own elements,
+//! so the whole slice ends up uniformly shuffled just like `knuth_shuffle`.
+
+use crate::{knuth_shuffle::shuffle_with, rng::Rng};
+
+// 小于该长度的块直接用 Fisher-Yates
+const BLOCK_LEN: usize = 1 << 16;
+
+/// Shuffle the slice in parallel.
+/// `rng_factory` is called with a different id for every block and merge,
+/// so a deterministic factory gives a deterministic result.
+pub fn par_shuffle<T: Send, R>(input: &mut [T], rng_factory: impl Fn(usize) -> R + Sync)
+where
+    R: Rng,
+{
+    merge_shuffle(input, BLOCK_LEN, 0, &rng_factory);
+}
+
+fn merge_shuffle<T: Send, R, F>(input: &mut [T], block_len: usize, id: usize, rng_factory: &F)
+where
+    R: Rng,
+    F: Fn(usize) -> R + Sync,
+{
+    if input.len() <= block_len {
+        shuffle_with(input, &mut rng_factory(id));
+        return;
+    }
+
+    // 按二叉堆的方式给子任务编号，保证每个 id 只用一次
+    let middle = input.len() / 2;
+    let (left, right) = input.split_at_mut(middle);
+    rayon::join(
+        || merge_shuffle(left, block_len, 2 * id + 1, rng_factory),
+        || merge_shuffle(right, block_len, 2 * id + 2, rng_factory),
+    );
+    merge(input, middle, &mut rng_factory(id));
+}
+
+/// Merge two shuffled halves `input[..middle]` and `input[middle..]` by random interleaving.
+fn merge<T, R: Rng>(input: &mut [T], middle: usize, rng: &mut R) {
+    let len = input.len();
+    let mut bits = BitSource::new();
+
+    // input[..i] 是已经合并好的部分，input[j..] 是右半部分尚未取用的元素
+    let mut i = 0;
+    let mut j = middle;
+    loop {
+        if bits.next(rng) {
+            // 取右半部分的元素
+            if j == len {
+                break;
+            }
+            input.swap(i, j);
+            j += 1;
+        } else if i == j {
+            // 左半部分已经用完
+            break;
+        }
+        i += 1;
+    }
+
+    // 其中一侧用完后，剩下的元素逐个随机插入到前面，和 Fisher-Yates 的一步相同
+    while i < len {
+        input.swap(i, rng.gen_range(i + 1));
+        i += 1;
+    }
+}
+
+/// Hand out random bits one by one, 64 bits per `next_u64`.
+struct BitSource {
+    bits: u64,
+    remaining: u32,
+}
+
+impl BitSource {
+    fn new() -> Self {
+        Self { bits: 0, remaining: 0 }
+    }
+
+    fn next<R: Rng>(&mut self, rng: &mut R) -> bool {
+        if self.remaining == 0 {
+            self.bits = rng.next_u64();
+            self.remaining = 64;
+        }
+        let bit = self.bits & 1 == 1;
+        self.bits >>= 1;
+        self.remaining -= 1;
+        bit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::prng::Xoshiro256StarStar;
+    use super::{merge_shuffle, par_shuffle};
+
+    #[test]
+    fn test_par_shuffle() {
+        let mut input: Vec<u32> = (0..200_000).collect();
+        par_shuffle(&mut input, |id| Xoshiro256StarStar::seed_from_u64(id as u64));
+        assert_ne!(input, (0..200_000).collect::<Vec<u32>>());
+
+        input.sort();
+        assert_eq!(input, (0..200_000).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn test_par_shuffle_deterministic() {
+        let mut a: Vec<u32> = (0..1000).collect();
+        let mut b: Vec<u32> = (0..1000).collect();
+        merge_shuffle(&mut a, 8, 0, &|id| Xoshiro256StarStar::seed_from_u64(id as u64));
+        merge_shuffle(&mut b, 8, 0, &|id| Xoshiro256StarStar::seed_from_u64(id as u64));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_par_shuffle_uniform() {
+        // 块长为 1 时所有的打乱都来自 merge
+        let mut counts = HashMap::new();
+        for seed in 0..24000u64 {
+            let mut input = [0, 1, 2, 3];
+            merge_shuffle(&mut input, 1, 0, &|id| Xoshiro256StarStar::seed_from_u64(seed * 8 + id as u64));
+            *counts.entry(input).or_insert(0) += 1;
+        }
+        // 4 个元素共 24 种排列，期望每种出现 1000 次
+        assert_eq!(counts.len(), 24);
+        for count in counts.values() {
+            assert!((800..1200).contains(count), "{:?}", counts);
+        }
+    }
+}
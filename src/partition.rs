@@ -0,0 +1,112 @@
+//! Partition algorithm
+
+use std::cmp::Ordering;
+
+/// This function implements the Dutch national flag partition.
+/// Return `(lt_end, gt_start)` so that `input[..lt_end] < pivot`,
+/// `input[lt_end..gt_start] == pivot` and `input[gt_start..] > pivot`.
+pub fn three_way_partition<T: Ord>(input: &mut [T], pivot: &T) -> (usize, usize) {
+    three_way_partition_by(input, pivot, T::cmp)
+}
+
+/// Same as `three_way_partition`, but compare with `cmp`.
+pub fn three_way_partition_by<T, F: FnMut(&T, &T) -> Ordering>(
+    input: &mut [T],
+    pivot: &T,
+    mut cmp: F,
+) -> (usize, usize) {
+    // input[..lt] 小于 pivot，input[lt..i] 等于 pivot，input[gt..] 大于 pivot
+    // input[i..gt] 是还未处理的区域
+    let mut lt = 0;
+    let mut i = 0;
+    let mut gt = input.len();
+
+    while i < gt {
+        match cmp(&input[i], pivot) {
+            Ordering::Less => {
+                input.swap(lt, i);
+                lt += 1;
+                i += 1;
+            }
+            Ordering::Greater => {
+                // 换过来的值还未处理，所以 i 不动
+                gt -= 1;
+                input.swap(i, gt);
+            }
+            Ordering::Equal => {
+                i += 1;
+            }
+        }
+    }
+
+    (lt, gt)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{prng::Xoshiro256StarStar, rng::Rng};
+    use super::{three_way_partition, three_way_partition_by};
+
+    fn check(input: &mut [i32], pivot: i32) -> (usize, usize) {
+        let mut expected = input.to_vec();
+        let (lt_end, gt_start) = three_way_partition(input, &pivot);
+        assert!(input[..lt_end].iter().all(|&x| x < pivot));
+        assert!(input[lt_end..gt_start].iter().all(|&x| x == pivot));
+        assert!(input[gt_start..].iter().all(|&x| x > pivot));
+
+        let mut output = input.to_vec();
+        output.sort();
+        expected.sort();
+        assert_eq!(output, expected);
+        (lt_end, gt_start)
+    }
+
+    #[test]
+    fn test_three_way_partition() {
+        let mut input = [3, 1, 3, 2, 3, 1];
+        assert_eq!(check(&mut input, 3), (3, 6));
+
+        let mut input = [3, 1, 3, 2, 3, 1];
+        assert_eq!(check(&mut input, 2), (2, 3));
+
+        let mut input = [5, 0, 9, 5, 1, 7, 5];
+        assert_eq!(check(&mut input, 5), (2, 5));
+    }
+
+    #[test]
+    fn test_three_way_partition_by() {
+        let mut input = [3, 1, 3, 2, 3, 1];
+        // 按降序划分
+        assert_eq!(three_way_partition_by(&mut input, &2, |a, b| b.cmp(a)), (3, 4));
+        assert_eq!(input[..3], [3, 3, 3]);
+        assert_eq!(input[3], 2);
+        assert_eq!(input[4..], [1, 1]);
+    }
+
+    #[test]
+    fn test_three_way_partition_edge() {
+        let mut input: [i32; 0] = [];
+        assert_eq!(check(&mut input, 1), (0, 0));
+
+        let mut input = [2, 2, 2];
+        assert_eq!(check(&mut input, 2), (0, 3));
+        assert_eq!(check(&mut input, 3), (3, 3));
+        assert_eq!(check(&mut input, 1), (0, 0));
+    }
+
+    #[test]
+    fn test_three_way_partition_random() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for len in 0..50 {
+            let input: Vec<i32> = (0..len).map(|_| rng.gen_range(10) as i32).collect();
+            // pivot 可能不在输入中，也可能小于或大于所有值
+            for pivot in -1..11 {
+                let mut output = input.clone();
+                let (lt_end, gt_start) = check(&mut output, pivot);
+                // 边界等于小于 pivot 和不大于 pivot 的元素个数
+                assert_eq!(lt_end, input.iter().filter(|&&x| x < pivot).count());
+                assert_eq!(gt_start, input.iter().filter(|&&x| x <= pivot).count());
+            }
+        }
+    }
+}
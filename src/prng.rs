@@ -0,0 +1,145 @@
+//! Built-in pseudo random number generator
+
+use std::{cell::RefCell, collections::hash_map::RandomState, hash::{BuildHasher, Hasher}};
+use crate::rng::Rng;
+
+/// SplitMix64, only used to expand a `u64` seed into the xoshiro state.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// The xoshiro256** generator.
+/// Fast and small, with a period of 2^256 - 1. It is NOT cryptographically secure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Xoshiro256StarStar {
+    s: [u64; 4],
+}
+
+impl Xoshiro256StarStar {
+    /// Build from a 32 bytes seed, read as four little endian `u64`.
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        // 全 0 的状态会一直输出 0，此时退回到 seed_from_u64
+        if seed.iter().all(|&b| b == 0) {
+            return Self::seed_from_u64(0);
+        }
+
+        let mut s = [0; 4];
+        for (i, chunk) in seed.chunks_exact(8).enumerate() {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(chunk);
+            s[i] = u64::from_le_bytes(bytes);
+        }
+        Self { s }
+    }
+
+    /// Build from a `u64` seed, expanded by SplitMix64.
+    pub fn seed_from_u64(mut state: u64) -> Self {
+        // SplitMix64 的输出不会全为 0
+        let mut s = [0; 4];
+        for x in s.iter_mut() {
+            *x = splitmix64(&mut state);
+        }
+        Self { s }
+    }
+}
+
+impl Rng for Xoshiro256StarStar {
+    fn next_u64(&mut self) -> u64 {
+        let result = self.s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.s[1] << 17;
+
+        self.s[2] ^= self.s[0];
+        self.s[3] ^= self.s[1];
+        self.s[1] ^= self.s[2];
+        self.s[0] ^= self.s[3];
+        self.s[2] ^= t;
+        self.s[3] = self.s[3].rotate_left(45);
+
+        result
+    }
+}
+
+thread_local! {
+    static THREAD_RNG: RefCell<Xoshiro256StarStar> = {
+        // RandomState 每次创建都会带上不同的随机 key，借它拿到一个随机种子
+        let seed = RandomState::new().build_hasher().finish();
+        RefCell::new(Xoshiro256StarStar::seed_from_u64(seed))
+    };
+}
+
+/// Run `f` with the thread-local generator, which is seeded randomly once per thread.
+pub fn with_thread_rng<F: FnOnce(&mut Xoshiro256StarStar) -> O, O>(f: F) -> O {
+    THREAD_RNG.with(|rng| f(&mut rng.borrow_mut()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rng::Rng;
+    use super::{with_thread_rng, Xoshiro256StarStar};
+
+    #[test]
+    fn test_reference_output() {
+        // 参考实现中 s = [1, 2, 3, 4] 的前几个输出
+        let mut rng = Xoshiro256StarStar { s: [1, 2, 3, 4] };
+        assert_eq!(rng.next_u64(), 11520);
+        assert_eq!(rng.next_u64(), 0);
+        assert_eq!(rng.next_u64(), 1509978240);
+        assert_eq!(rng.next_u64(), 1215971899390074240);
+    }
+
+    #[test]
+    fn test_deterministic_seed() {
+        let mut a = Xoshiro256StarStar::seed_from_u64(42);
+        let mut b = Xoshiro256StarStar::seed_from_u64(42);
+        let mut c = Xoshiro256StarStar::seed_from_u64(43);
+        let a: Vec<u64> = (0..16).map(|_| a.next_u64()).collect();
+        let b: Vec<u64> = (0..16).map(|_| b.next_u64()).collect();
+        let c: Vec<u64> = (0..16).map(|_| c.next_u64()).collect();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+
+        let mut zero = Xoshiro256StarStar::from_seed([0; 32]);
+        assert_ne!(zero.next_u64(), 0);
+    }
+
+    #[test]
+    fn test_quality_basics() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(7);
+        let mut values: Vec<u64> = (0..10000).map(|_| rng.next_u64()).collect();
+
+        // 每一位出现 1 的次数应接近一半
+        for bit in 0..64 {
+            let ones = values.iter().filter(|&&v| v & (1 << bit) != 0).count();
+            assert!((4700..5300).contains(&ones), "bit {} has {} ones", bit, ones);
+        }
+
+        // 在这么短的序列里不应出现重复，即没有进入短周期
+        values.sort();
+        values.dedup();
+        assert_eq!(values.len(), 10000);
+    }
+
+    #[test]
+    fn test_gen_range() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(7);
+        let mut counts = [0; 3];
+        for _ in 0..30000 {
+            counts[rng.gen_range(3)] += 1;
+        }
+        for count in counts {
+            assert!((9000..11000).contains(&count), "{:?}", counts);
+        }
+        assert_eq!(rng.gen_range(1), 0);
+    }
+
+    #[test]
+    fn test_thread_rng() {
+        let a = with_thread_rng(|rng| rng.next_u64());
+        let b = with_thread_rng(|rng| rng.next_u64());
+        assert_ne!(a, b);
+    }
+}
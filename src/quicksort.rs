@@ -0,0 +1,160 @@
+//! Quicksort algorithm
+
+use std::cmp::Ordering;
+use crate::{insertion_sort::insertion_sort_by, partition::three_way_partition_by, prng::with_thread_rng, rng::Rng};
+
+// 小于等于该长度时使用插入排序
+const INSERTION_SORT_THRESHOLD: usize = 16;
+
+/// Sort the slice with randomized quicksort.
+/// Not stable. Average O(n log n), and the stack depth is O(log n).
+pub fn quicksort<T: Ord>(input: &mut [T]) {
+    quicksort_by(input, T::cmp);
+}
+
+/// Same as `quicksort`, but compare with `cmp`.
+pub fn quicksort_by<T, F: FnMut(&T, &T) -> Ordering>(input: &mut [T], mut cmp: F) {
+    with_thread_rng(|rng| quicksort_with(input, None, &mut cmp, rng));
+}
+
+/// `pred` is the pivot of an ancestor partition that bounds `input` from the left,
+/// so every element of `input` is not less than it.
+fn quicksort_with<'a, T, F, R>(mut input: &'a mut [T], mut pred: Option<&'a T>, cmp: &mut F, rng: &mut R)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    R: Rng,
+{
+    // 只递归较短的一侧，较长的一侧留在循环里继续处理，所以递归深度不超过 log2(n)
+    loop {
+        if input.len() <= INSERTION_SORT_THRESHOLD {
+            insertion_sort_by(input, &mut *cmp);
+            return;
+        }
+
+        // 随机选取 pivot，避免有序输入退化到 O(n^2)
+        let pivot = rng.gen_range(input.len());
+
+        // pivot 与 pred 相等时，它就是区域内的最小值，说明该值大量重复
+        // 用三路划分把与它相等的值一次性排除，只需继续排序大于它的部分
+        if let Some(pred) = pred {
+            if cmp(&input[pivot], pred) == Ordering::Equal {
+                let (_, gt_start) = three_way_partition_by(input, pred, &mut *cmp);
+                input = &mut input[gt_start..];
+                continue;
+            }
+        }
+
+        let index = partition(input, pivot, cmp);
+        let (left, right) = input.split_at_mut(index);
+        let (pivot, right) = right.split_first_mut().unwrap();
+        let pivot: &'a T = pivot;
+        if left.len() < right.len() {
+            quicksort_with(left, pred, cmp, rng);
+            input = right;
+            pred = Some(pivot);
+        } else {
+            quicksort_with(right, Some(pivot), cmp, rng);
+            input = left;
+        }
+    }
+}
+
+/// Partition `input` around the value at `pivot` and return its final index.
+/// 左侧都小于等于 pivot 值，右侧都大于等于 pivot 值
+/// 左右两侧都会停在与 pivot 相等的值上并交换，所以大量重复值时也能分得均匀
+pub(crate) fn partition<T, F: FnMut(&T, &T) -> Ordering>(input: &mut [T], pivot: usize, cmp: &mut F) -> usize {
+    // 先把 pivot 放到最前面
+    input.swap(0, pivot);
+
+    // input[1..i] 都小于等于 pivot 值，input[j+1..] 都大于等于 pivot 值
+    let mut i = 1;
+    let mut j = input.len() - 1;
+    loop {
+        while i <= j && cmp(&input[i], &input[0]) == Ordering::Less {
+            i += 1;
+        }
+        while i <= j && cmp(&input[j], &input[0]) == Ordering::Greater {
+            j -= 1;
+        }
+        if i >= j {
+            break;
+        }
+        input.swap(i, j);
+        i += 1;
+        j -= 1;
+    }
+
+    // 此时 input[j] 小于等于 pivot 值，与 pivot 交换即可
+    input.swap(0, j);
+    j
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{knuth_shuffle::shuffle_with, prng::Xoshiro256StarStar, rng::Rng, sort_test_util::check_sort};
+    use super::{quicksort, quicksort_by};
+
+    fn check(input: Vec<i32>) {
+        check_sort(input, quicksort);
+    }
+
+    #[test]
+    fn test_quicksort_random() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for len in 0..100 {
+            let input: Vec<i32> = (0..len).map(|_| rng.gen_range(50) as i32).collect();
+            check(input);
+        }
+        let mut input: Vec<i32> = (0..10000).collect();
+        shuffle_with(&mut input, &mut rng);
+        check(input);
+    }
+
+    #[test]
+    fn test_quicksort_sorted() {
+        check((0..10000).collect());
+    }
+
+    #[test]
+    fn test_quicksort_reverse_sorted() {
+        check((0..10000).rev().collect());
+    }
+
+    #[test]
+    fn test_quicksort_all_equal() {
+        check(vec![7; 10000]);
+    }
+
+    #[test]
+    fn test_quicksort_duplicate_heavy() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for bound in [1, 2, 3, 10] {
+            let input: Vec<i32> = (0..10000).map(|_| rng.gen_range(bound) as i32).collect();
+            check(input);
+        }
+    }
+
+    #[test]
+    fn test_quicksort_by() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        let mut input: Vec<(u32, u32)> = (0..1000).map(|i| (rng.gen_range(10) as u32, i)).collect();
+        let mut expected = input.clone();
+        expected.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+        // 按 key 降序，key 相同时按 tag 升序
+        quicksort_by(&mut input, |a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        assert_eq!(input, expected);
+
+        let mut input = vec!["ccc", "a", "bb"];
+        quicksort_by(&mut input, |a, b| a.len().cmp(&b.len()));
+        assert_eq!(input, vec!["a", "bb", "ccc"]);
+    }
+
+    #[test]
+    fn test_quicksort_large() {
+        // 较长的一侧在循环中处理，大数组也不会栈溢出
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        let input: Vec<i32> = (0..200_000).map(|_| rng.gen_range(1000) as i32).collect();
+        check(input);
+    }
+}
@@ -0,0 +1,69 @@
+//! Radix sort algorithm
+
+// 每一轮处理 8 位，共 4 轮
+const RADIX_BITS: u32 = 8;
+const BUCKETS: usize = 1 << RADIX_BITS;
+
+/// Sort `u32` values with a least-significant-digit radix sort.
+/// Four passes of 8 bits each with one scratch buffer, so it is O(n). Stable.
+pub fn radix_sort_u32(input: &mut [u32]) {
+    let mut scratch = vec![0; input.len()];
+    // 每一轮在 input 和 scratch 之间来回分配，4 轮之后结果回到 input
+    let (mut src, mut dst) = (input, &mut scratch[..]);
+    for pass in 0..u32::BITS / RADIX_BITS {
+        let shift = pass * RADIX_BITS;
+        let digit = |value: u32| (value >> shift) as usize & (BUCKETS - 1);
+
+        // 统计每个桶的数量，再转换为每个桶的起始位置
+        let mut offsets = [0usize; BUCKETS];
+        for &value in src.iter() {
+            offsets[digit(value)] += 1;
+        }
+        let mut start = 0;
+        for offset in offsets.iter_mut() {
+            let count = *offset;
+            *offset = start;
+            start += count;
+        }
+
+        // 按顺序放入桶中，同一个桶内保持原来的相对顺序
+        for &value in src.iter() {
+            let bucket = digit(value);
+            dst[offsets[bucket]] = value;
+            offsets[bucket] += 1;
+        }
+        std::mem::swap(&mut src, &mut dst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{prng::Xoshiro256StarStar, rng::Rng, sort_test_util::check_sort};
+    use super::radix_sort_u32;
+
+    fn check(input: Vec<u32>) {
+        check_sort(input, radix_sort_u32);
+    }
+
+    #[test]
+    fn test_radix_sort_u32() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for len in (0..100).chain([1000, 10000]) {
+            check((0..len).map(|_| rng.next_u64() as u32).collect());
+        }
+        // 只有低位不同
+        check((0..1000).map(|_| rng.gen_range(300) as u32).collect());
+    }
+
+    #[test]
+    fn test_radix_sort_u32_sorted() {
+        check((0..10000).collect());
+        check((0..10000).rev().collect());
+        check(vec![42; 1000]);
+    }
+
+    #[test]
+    fn test_radix_sort_u32_full_range() {
+        check(vec![u32::MAX, 0, 1 << 31, u32::MAX - 1, 1, (1 << 31) - 1, 0x00ff_ff00, 0xff00_00ff]);
+    }
+}
@@ -1,5 +1,29 @@
-use std::{alloc::{alloc, Layout, dealloc}, ptr::{self, NonNull}, sync::atomic::{AtomicUsize, Ordering}};
+use std::{alloc::{alloc, Layout, dealloc}, mem::MaybeUninit, ops::Deref, ptr::{self, NonNull}, slice, sync::{atomic::{fence, AtomicBool, AtomicU64, AtomicUsize, Ordering}, Condvar, Mutex}, thread};
+use crate::error::AlgoError;
 
+/// Align the value to 64 bytes, so it sits on its own cache line.
+/// Writer updates tail and reader updates head, keeping them apart avoids false sharing.
+///
+/// Memory overhead: each padded value takes 64 bytes instead of its own size,
+/// so `RingBuffer` grows from 40 to 192 bytes on 64-bit targets. The element buffer is not affected.
+#[repr(align(64))]
+struct CachePadded<T> {
+    value: T,
+}
+
+impl<T> CachePadded<T> {
+    fn new(value: T) -> Self {
+        Self { value }
+    }
+}
+
+impl<T> Deref for CachePadded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
 
 /**
  * RingBuffer implementation
@@ -20,11 +44,38 @@ pub struct RingBuffer<T> {
     // Can't be modified in runtime.
     capacity: usize,
     // head position, read from head
-    head: AtomicUsize,
+    head: CachePadded<AtomicUsize>,
     // tail position, write after tail
-    tail: AtomicUsize,
+    tail: CachePadded<AtomicUsize>,
     // Counter of writer and reader, maximum size is 2.
     counter: AtomicUsize,
+    // Number of elements ever pushed and popped, only for statistics, so they are relaxed.
+    total_pushed: AtomicU64,
+    total_popped: AtomicU64,
+    // Used by `RingbufferReader::drain_until_closed` to sleep until the writer pushes or is dropped.
+    // The writer only takes the lock when `waiting` is set, so plain pushes stay lock free.
+    wakeup: Mutex<()>,
+    wakeup_cv: Condvar,
+    waiting: AtomicBool,
+}
+
+/// A consistent view of the `RingBuffer` positions taken at a single moment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RingState {
+    pub head: usize,
+    pub tail: usize,
+    pub len: usize,
+    pub capacity: usize,
+}
+
+impl RingState {
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity
+    }
 }
 
 impl<T> RingBuffer<T> {
@@ -32,17 +83,38 @@ impl<T> RingBuffer<T> {
         assert_ne!(cap, 0, "Capacity must be greater than 0");
         assert!(cap.is_power_of_two(), "Capacity must be a power of two");
 
-        
-        let layout = Layout::array::<T>(cap).unwrap();
+        match Self::try_with_capacity(cap) {
+            Ok(buffer) => buffer,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Same as `with_capacity`, but return `AlgoError::InvalidCapacity` instead of panicking
+    /// if `cap` is 0, not a power of two, or too large to allocate.
+    pub fn try_with_capacity(cap: usize) -> Result<Self, AlgoError> {
+        if !cap.is_power_of_two() {
+            return Err(AlgoError::InvalidCapacity(cap));
+        }
+
+        // 总大小超过 isize::MAX 时 Layout 会报错，内存不足时 alloc 返回空指针
+        let layout = Layout::array::<T>(cap).map_err(|_| AlgoError::InvalidCapacity(cap))?;
         let buf = unsafe { alloc(layout) as *mut T };
+        if buf.is_null() {
+            return Err(AlgoError::InvalidCapacity(cap));
+        }
 
-        Self {
+        Ok(Self {
             buf,
             capacity: cap,
-            head: AtomicUsize::new(0),
-            tail: AtomicUsize::new(0),
+            head: CachePadded::new(AtomicUsize::new(0)),
+            tail: CachePadded::new(AtomicUsize::new(0)),
             counter: AtomicUsize::new(2),
-        }
+            total_pushed: AtomicU64::new(0),
+            total_popped: AtomicU64::new(0),
+            wakeup: Mutex::new(()),
+            wakeup_cv: Condvar::new(),
+            waiting: AtomicBool::new(false),
+        })
     }
 
     /// Turn position(head and tail) into index with offset.
@@ -66,6 +138,7 @@ impl<T> RingBuffer<T> {
         }
 
         self.tail.fetch_add(1, Ordering::AcqRel);
+        self.total_pushed.fetch_add(1, Ordering::Relaxed);
         true
     }
 
@@ -81,9 +154,28 @@ impl<T> RingBuffer<T> {
         };
 
         self.head.fetch_add(1, Ordering::AcqRel);
+        self.total_popped.fetch_add(1, Ordering::Relaxed);
         res
     }
 
+    /// Pop the head element with its sequence number.
+    /// The sequence number is the head position before popping, which increases by one per pop.
+    pub fn pop_with_seq(&mut self) -> Option<(u64, T)> {
+        let seq = self.head.load(Ordering::Acquire) as u64;
+        self.pop().map(|value| (seq, value))
+    }
+
+    /// Look at the element `n` positions from head without popping it, 0 is the next element to pop.
+    /// Return `None` if there are no more than `n` elements.
+    pub fn peek_nth(&self, n: usize) -> Option<&T> {
+        // 只有 reader 会移动 head，而 head 到 tail 之间的位置 writer 不会再写入，所以引用一直有效
+        if n >= self.len() {
+            return None;
+        }
+        let index = Self::position_to_index(self.capacity, self.head.load(Ordering::Acquire).wrapping_add(n));
+        unsafe { Some(&*self.buf.add(index)) }
+    }
+
     pub fn len(&self) -> usize {
         self.tail.load(Ordering::Acquire).wrapping_sub(self.head.load(Ordering::Acquire))
     }
@@ -96,6 +188,180 @@ impl<T> RingBuffer<T> {
         self.len() == self.capacity
     }
 
+    /// Push the new element, popping the head element first if it is full.
+    /// Return the popped element, so the buffer keeps the latest `capacity` elements.
+    /// It touches head, so it must not be used while a reader is popping.
+    pub fn push_overwrite(&mut self, value: T) -> Option<T> {
+        let evicted = if self.is_full() { self.pop() } else { None };
+        self.push(value);
+        evicted
+    }
+
+    /// Number of elements that can be pushed before it is full.
+    pub fn free_slots(&self) -> usize {
+        self.capacity - self.len()
+    }
+
+    /// Write the element `offset` positions after tail without moving tail, the reader can't see it yet.
+    /// Return `false` if that slot is not free. Publish the written elements with `publish`.
+    pub(crate) fn write_unpublished(&mut self, offset: usize, value: T) -> bool {
+        if offset >= self.free_slots() {
+            return false;
+        }
+        let index = Self::position_to_index(self.capacity, self.tail.load(Ordering::Acquire).wrapping_add(offset));
+        unsafe {
+            ptr::write(self.buf.add(index), value);
+        }
+        true
+    }
+
+    /// Make the `count` elements written by `write_unpublished` visible to the reader by moving tail once.
+    pub(crate) fn publish(&mut self, count: usize) {
+        if count == 0 {
+            return;
+        }
+        self.tail.fetch_add(count, Ordering::AcqRel);
+        self.total_pushed.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    /// Whether `position` is free to be written, i.e. the reader has popped the position `capacity` before it.
+    pub(crate) fn has_room_at(&self, position: usize) -> bool {
+        position.wrapping_sub(self.head.load(Ordering::Acquire)) < self.capacity
+    }
+
+    /// Write the element at `position` without moving tail.
+    ///
+    /// # Safety
+    /// The caller must be the only one writing `position`, and `has_room_at(position)` must have returned `true`.
+    pub(crate) unsafe fn write_at(&self, position: usize, value: T) {
+        let index = Self::position_to_index(self.capacity, position);
+        ptr::write(self.buf.add(index), value);
+    }
+
+    /// Move tail past `position` once every earlier position is committed, so the reader sees elements in position order.
+    pub(crate) fn commit_at(&self, position: usize) {
+        // 前面的位置还没写完时等待，写完的写入端按位置顺序依次移动 tail
+        while self.tail.load(Ordering::Acquire) != position {
+            thread::yield_now();
+        }
+        self.tail.store(position.wrapping_add(1), Ordering::Release);
+        self.total_pushed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Number of elements ever pushed and popped, `(total_pushed, total_popped)`.
+    /// Unlike the positions they are never reset, elements evicted by `push_overwrite` count as popped.
+    /// The two loads are relaxed and independent, so use `snapshot` for a consistent `len`.
+    pub fn totals(&self) -> (u64, u64) {
+        (self.total_pushed.load(Ordering::Relaxed), self.total_popped.load(Ordering::Relaxed))
+    }
+
+    /// Push all the values, or none of them if there is not enough room.
+    /// The original `Vec` is given back in `Err` when nothing is pushed.
+    pub fn push_all_or_none(&mut self, values: Vec<T>) -> Result<(), Vec<T>> {
+        // 只有 writer 会减少空位，检查之后空位只会变多，所以后面的 push 不会失败
+        if self.free_slots() < values.len() {
+            return Err(values);
+        }
+        for value in values {
+            let pushed = self.push(value);
+            debug_assert!(pushed);
+        }
+        Ok(())
+    }
+
+    /// Read head and tail as one observation, and derive the rest from them.
+    /// Calling `len()` and `is_full()` separately may see different states.
+    pub fn snapshot(&self) -> RingState {
+        loop {
+            // tail 只会增加，如果前后两次读到的 tail 相同，
+            // 说明读 head 的时刻 tail 就是这个值，两者属于同一时刻的状态
+            let tail = self.tail.load(Ordering::Acquire);
+            let head = self.head.load(Ordering::Acquire);
+            if self.tail.load(Ordering::Acquire) == tail {
+                return RingState {
+                    head,
+                    tail,
+                    len: tail.wrapping_sub(head),
+                    capacity: self.capacity,
+                };
+            }
+        }
+    }
+
+    /// Swap two elements by their logical offset from head.
+    /// 0 is the next element to pop.
+    ///
+    /// Panics if `i` or `j` is out of bounds.
+    pub fn swap(&mut self, i: usize, j: usize) {
+        let len = self.len();
+        assert!(i < len, "index out of bounds: the len is {} but the index is {}", len, i);
+        assert!(j < len, "index out of bounds: the len is {} but the index is {}", len, j);
+
+        let head = self.head.load(Ordering::Acquire);
+        let i = Self::position_to_index(self.capacity, head.wrapping_add(i));
+        let j = Self::position_to_index(self.capacity, head.wrapping_add(j));
+        unsafe {
+            ptr::swap(self.buf.add(i), self.buf.add(j));
+        }
+    }
+
+    /// Whether the live elements occupy one slice of the buffer without wrapping.
+    pub fn is_contiguous(&self) -> bool {
+        let state = self.snapshot();
+        Self::position_to_index(self.capacity, state.head) + state.len <= self.capacity
+    }
+
+    /// Rotate the elements in place so they start at buffer index 0, and return them as one slice.
+    /// The positions are reset to `head = 0` and `tail = len`,
+    /// so the sequence numbers of `pop_with_seq` restart from 0.
+    pub fn make_contiguous(&mut self) -> &[T] {
+        let len = self.len();
+        let head = Self::position_to_index(self.capacity, self.head.load(Ordering::Acquire));
+        unsafe {
+            // 缓冲区中有未初始化的位置，所以按 MaybeUninit 整体旋转
+            let buf = slice::from_raw_parts_mut(self.buf as *mut MaybeUninit<T>, self.capacity);
+            buf.rotate_left(head);
+        }
+        self.head.store(0, Ordering::Release);
+        self.tail.store(len, Ordering::Release);
+
+        unsafe { slice::from_raw_parts(self.buf, len) }
+    }
+
+    /// Move the elements into a new buffer of `new_cap`, keeping their logical order.
+    /// The positions are reset to `head = 0` and `tail = len`.
+    /// Return `false` and leave it unchanged if `new_cap` is less than `len`.
+    ///
+    /// Panics if `new_cap` is 0 or not a power of two.
+    pub fn resize(&mut self, new_cap: usize) -> bool {
+        assert_ne!(new_cap, 0, "Capacity must be greater than 0");
+        assert!(new_cap.is_power_of_two(), "Capacity must be a power of two");
+
+        let len = self.len();
+        if new_cap < len {
+            return false;
+        }
+
+        let head = self.head.load(Ordering::Acquire);
+        let layout = Layout::array::<T>(new_cap).unwrap();
+        let buf = unsafe { alloc(layout) as *mut T };
+        for offset in 0..len {
+            let index = Self::position_to_index(self.capacity, head.wrapping_add(offset));
+            unsafe {
+                // 按从 head 开始的逻辑顺序移动到新缓冲区的 0..len
+                ptr::write(buf.add(offset), ptr::read(self.buf.add(index)));
+            }
+        }
+
+        let old_layout = Layout::array::<T>(self.capacity).unwrap();
+        unsafe { dealloc(self.buf.cast(), old_layout) };
+        self.buf = buf;
+        self.capacity = new_cap;
+        self.head.store(0, Ordering::Release);
+        self.tail.store(len, Ordering::Release);
+        true
+    }
+
     // /// Turn position(head and tail) into index with offset.
     // /// When offset is [-1, `-len`], get the last elements pushed index.
     // /// When offset is [0, `len - 1`], get the items that were pushed the longest ago.
@@ -158,16 +424,41 @@ impl<T> RingBuffer<T> {
     //     })
     // }
 
-    pub fn release(&mut self) {
+    /// Wake the reader sleeping in `drain_until_closed`, if any.
+    fn wake_reader(&self) {
+        // 与 `drain_until_closed` 中的 fence 配对：要么这里看到 waiting，要么读取端看到新的 tail
+        fence(Ordering::SeqCst);
+        if self.waiting.load(Ordering::Relaxed) {
+            let _guard = self.wakeup.lock().unwrap();
+            self.wakeup_cv.notify_one();
+        }
+    }
+
+    /// Release one of the two handles, the last one drops the remaining elements and frees the buffer.
+    /// Return whether the buffer was freed.
+    pub fn release(&mut self) -> bool {
         if self.counter.fetch_sub(1, Ordering::AcqRel) == 1 {
             loop {
                 if self.pop().is_none() {
                     let layout = Layout::array::<T>(self.capacity).unwrap();
                     unsafe { dealloc(self.buf.cast(), layout) };
-                    return;
+                    return true;
                 }
             }
         }
+        false
+    }
+}
+
+impl<T> Drop for RingBuffer<T> {
+    fn drop(&mut self) {
+        // 通过 `ringbuffer` 创建时由两端的 `release` 释放，counter 此时不是 2
+        if *self.counter.get_mut() != 2 {
+            return;
+        }
+        while self.pop().is_some() {}
+        let layout = Layout::array::<T>(self.capacity).unwrap();
+        unsafe { dealloc(self.buf.cast(), layout) };
     }
 }
 
@@ -187,7 +478,12 @@ pub struct RingbufferWriter<T> {
 impl<T> RingbufferWriter<T> {
     pub fn push(&mut self, value: T) -> bool {
         unsafe {
-            self.inner.as_mut().push(value)
+            let inner = self.inner.as_mut();
+            let pushed = inner.push(value);
+            if pushed {
+                inner.wake_reader();
+            }
+            pushed
         }
     }
     
@@ -208,12 +504,94 @@ impl<T> RingbufferWriter<T> {
             self.inner.as_ref().is_full()
         }
     }
+
+    pub fn free_slots(&self) -> usize {
+        unsafe {
+            self.inner.as_ref().free_slots()
+        }
+    }
+
+    pub(crate) fn write_unpublished(&mut self, offset: usize, value: T) -> bool {
+        unsafe {
+            self.inner.as_mut().write_unpublished(offset, value)
+        }
+    }
+
+    pub(crate) fn publish(&mut self, count: usize) {
+        unsafe {
+            let inner = self.inner.as_mut();
+            inner.publish(count);
+            inner.wake_reader();
+        }
+    }
+
+    /// See `RingBuffer::has_room_at`.
+    pub(crate) fn has_room_at(&self, position: usize) -> bool {
+        unsafe {
+            self.inner.as_ref().has_room_at(position)
+        }
+    }
+
+    /// See `RingBuffer::write_at`.
+    ///
+    /// # Safety
+    /// The same as `RingBuffer::write_at`.
+    pub(crate) unsafe fn write_at(&self, position: usize, value: T) {
+        self.inner.as_ref().write_at(position, value)
+    }
+
+    /// See `RingBuffer::commit_at`, also wakes the reader.
+    pub(crate) fn commit_at(&self, position: usize) {
+        unsafe {
+            let inner = self.inner.as_ref();
+            inner.commit_at(position);
+            inner.wake_reader();
+        }
+    }
+
+    /// Issue a `Release` fence, so a reader that sees any later store of this thread,
+    /// such as the writer being closed by drop, also sees every element pushed before `flush`.
+    /// Each push already publishes its element with a `Release` on tail and dropping the writer
+    /// releases as well, so this only makes the guarantee explicit for shutdown sequences,
+    /// pair it with `RingbufferReader::is_writer_closed`, which loads with `Acquire`.
+    pub fn flush(&mut self) {
+        fence(Ordering::Release);
+    }
+
+    pub fn push_all_or_none(&mut self, values: Vec<T>) -> Result<(), Vec<T>> {
+        unsafe {
+            let inner = self.inner.as_mut();
+            let result = inner.push_all_or_none(values);
+            if result.is_ok() {
+                inner.wake_reader();
+            }
+            result
+        }
+    }
+
+    pub fn snapshot(&self) -> RingState {
+        unsafe {
+            self.inner.as_ref().snapshot()
+        }
+    }
+
+    pub fn totals(&self) -> (u64, u64) {
+        unsafe {
+            self.inner.as_ref().totals()
+        }
+    }
 }
 
 impl<T> Drop for RingbufferWriter<T> {
     fn drop(&mut self) {
         unsafe {
-            self.inner.as_mut().release();
+            let inner = self.inner.as_mut();
+            // 释放了缓冲区说明读取端已经析构，否则要唤醒等待关闭的读取端
+            // 读取端在 release 之后析构也没关系：`ringbuffer` 创建的 RingBuffer 本身从不释放，
+            // wake_reader 只用到其中的锁和条件变量
+            if !inner.release() {
+                inner.wake_reader();
+            }
         }
     }
 }
@@ -228,6 +606,18 @@ impl<T> RingbufferReader<T> {
             self.inner.as_mut().pop()
         }
     }
+
+    pub fn pop_with_seq(&mut self) -> Option<(u64, T)> {
+        unsafe {
+            self.inner.as_mut().pop_with_seq()
+        }
+    }
+
+    pub fn peek_nth(&self, n: usize) -> Option<&T> {
+        unsafe {
+            self.inner.as_ref().peek_nth(n)
+        }
+    }
     
     pub fn len(&self) -> usize {
         unsafe {
@@ -246,6 +636,65 @@ impl<T> RingbufferReader<T> {
             self.inner.as_ref().is_full()
         }
     }
+
+    pub fn snapshot(&self) -> RingState {
+        unsafe {
+            self.inner.as_ref().snapshot()
+        }
+    }
+
+    pub fn totals(&self) -> (u64, u64) {
+        unsafe {
+            self.inner.as_ref().totals()
+        }
+    }
+
+    /// Whether the writer has been dropped. Every element pushed before that is visible once it returns `true`,
+    /// so popping until empty after seeing it drains everything, see `RingbufferWriter::flush`.
+    pub fn is_writer_closed(&self) -> bool {
+        unsafe {
+            self.inner.as_ref().counter.load(Ordering::Acquire) == 1
+        }
+    }
+
+    /// Pop the head element, blocking while the buffer is empty and the writer is still alive.
+    /// Return `None` once the writer is dropped and the buffer is empty.
+    pub fn pop_wait(&mut self) -> Option<T> {
+        let inner = unsafe { self.inner.as_mut() };
+        loop {
+            if let Some(value) = inner.pop() {
+                return Some(value);
+            }
+
+            let guard = inner.wakeup.lock().unwrap();
+            inner.waiting.store(true, Ordering::Relaxed);
+            fence(Ordering::SeqCst);
+            // 先检查写入端是否已释放，释放前的写入此时一定可见
+            let closed = self.is_writer_closed();
+            let empty = inner.is_empty();
+            if empty && !closed {
+                // 可能是虚假唤醒，回到循环开头重新检查
+                drop(inner.wakeup_cv.wait(guard).unwrap());
+            } else {
+                drop(guard);
+            }
+            inner.waiting.store(false, Ordering::Relaxed);
+
+            if empty && closed {
+                return None;
+            }
+        }
+    }
+
+    /// Pop every element until the writer is dropped and the buffer is empty, and return them in order.
+    /// Blocks while the buffer is empty and the writer is still alive.
+    pub fn drain_until_closed(&mut self) -> Vec<T> {
+        let mut result = Vec::new();
+        while let Some(value) = self.pop_wait() {
+            result.push(value);
+        }
+        result
+    }
 }
 
 impl<T> Drop for RingbufferReader<T> {
@@ -256,6 +705,31 @@ impl<T> Drop for RingbufferReader<T> {
     }
 }
 
+impl<T> IntoIterator for RingbufferReader<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    /// Pop the available elements until it is empty.
+    /// It does not wait for the writer: the iterator stops at the first empty pop.
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { reader: self }
+    }
+}
+
+/// Iterator consuming a `RingbufferReader`, see `RingbufferReader::into_iter`.
+/// The buffer is released when the iterator is dropped, the same as the reader.
+pub struct IntoIter<T> {
+    reader: RingbufferReader<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.reader.pop()
+    }
+}
+
 // unsafe impl<T: Sync> Sync for RingBuffer<T> {}
 // unsafe impl<T: Send> Send for RingBuffer<T> {}
 
@@ -267,8 +741,8 @@ unsafe impl<T: Send> Send for RingbufferReader<T> {}
 
 #[cfg(test)]
 mod tests {
-    use std::{sync::Mutex, thread};
-    use crate::ringbuffer::ringbuffer;
+    use std::{sync::{atomic::{AtomicUsize, Ordering}, Arc, Mutex}, thread};
+    use crate::{error::AlgoError, ringbuffer::{ringbuffer, RingBuffer, RingState}};
 
     #[test]
     fn test_ring_buffer() {
@@ -318,6 +792,262 @@ mod tests {
         assert_eq!(reader.is_empty(), false);
     }
 
+    #[test]
+    fn test_cache_padded() {
+        let buf: RingBuffer<u8> = RingBuffer::with_capacity(4);
+        let head = &*buf.head as *const AtomicUsize as usize;
+        let tail = &*buf.tail as *const AtomicUsize as usize;
+        // head 和 tail 不在同一个缓存行上
+        assert_eq!(head % 64, 0);
+        assert_eq!(tail % 64, 0);
+        assert_ne!(head, tail);
+    }
+
+    #[test]
+    fn test_pop_with_seq() {
+        let (mut writer, mut reader) = ringbuffer(4);
+        assert_eq!(reader.pop_with_seq(), None);
+
+        let mut expected = 0;
+        for round in 0..5 {
+            for i in 0..3 {
+                writer.push(round * 3 + i);
+            }
+            while let Some((seq, value)) = reader.pop_with_seq() {
+                assert_eq!(seq, expected);
+                assert_eq!(value, expected);
+                expected += 1;
+            }
+        }
+        // 已经多次跨越缓冲区末尾
+        assert_eq!(expected, 15);
+    }
+
+    #[test]
+    fn test_make_contiguous() {
+        let mut buf = RingBuffer::with_capacity(8);
+        assert!(buf.is_contiguous());
+        assert_eq!(buf.make_contiguous(), &[] as &[i32]);
+
+        for i in 0..6 {
+            buf.push(i);
+        }
+        for _ in 0..4 {
+            buf.pop();
+        }
+        for i in 6..11 {
+            buf.push(i);
+        }
+        // 8 9 10 |start 4 5 6 7
+        assert!(!buf.is_contiguous());
+        assert_eq!(buf.make_contiguous(), &[4, 5, 6, 7, 8, 9, 10]);
+        assert!(buf.is_contiguous());
+
+        // 旋转之后仍能正常读写
+        buf.push(11);
+        assert!(buf.is_full());
+        for i in 4..12 {
+            assert_eq!(buf.pop(), Some(i));
+        }
+        assert_eq!(buf.pop(), None);
+    }
+
+    #[test]
+    fn test_swap() {
+        let mut buf = RingBuffer::with_capacity(4);
+        buf.push(0);
+        buf.push(1);
+        buf.pop();
+        buf.push(2);
+        buf.push(3);
+        buf.push(4);
+        // 4 |start 1 2 3
+        buf.swap(0, 3);
+        buf.swap(1, 1);
+        assert_eq!(buf.pop(), Some(4));
+        assert_eq!(buf.pop(), Some(2));
+        assert_eq!(buf.pop(), Some(3));
+        assert_eq!(buf.pop(), Some(1));
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut buf = RingBuffer::with_capacity(8);
+        // 先推入再弹出，使内容跨越缓冲区末尾
+        for i in 0..6 {
+            buf.push(i);
+        }
+        for _ in 0..6 {
+            buf.pop();
+        }
+        for i in 0..5 {
+            buf.push(i);
+        }
+        assert!(!buf.is_contiguous());
+
+        assert!(buf.resize(16));
+        assert_eq!(buf.snapshot(), RingState { head: 0, tail: 5, len: 5, capacity: 16 });
+        // 扩容后可以继续写入到新的容量
+        for i in 5..16 {
+            assert!(buf.push(i));
+        }
+        assert!(buf.is_full());
+
+        let mut output = Vec::new();
+        while let Some(value) = buf.pop() {
+            output.push(value);
+        }
+        assert_eq!(output, (0..16).collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn test_resize_too_small() {
+        let mut buf = RingBuffer::with_capacity(8);
+        for i in 0..5 {
+            buf.push(i);
+        }
+        assert!(!buf.resize(4));
+        assert_eq!(buf.len(), 5);
+
+        // 可以缩小到不小于 len 的容量
+        assert!(buf.resize(8));
+        assert_eq!(buf.pop(), Some(0));
+        assert!(buf.resize(4));
+        assert_eq!(buf.snapshot(), RingState { head: 0, tail: 4, len: 4, capacity: 4 });
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let (mut writer, reader) = ringbuffer(8);
+        for i in 0..4 {
+            writer.push(i);
+        }
+
+        let mut output = Vec::new();
+        for value in reader {
+            output.push(value);
+        }
+        assert_eq!(output, vec![0, 1, 2, 3]);
+        // reader 已经被消耗，这里的 push 不会被读到
+        assert!(writer.push(4));
+    }
+
+    #[test]
+    fn test_push_overwrite() {
+        let mut buf = RingBuffer::with_capacity(4);
+        for i in 0..4 {
+            assert_eq!(buf.push_overwrite(i), None);
+        }
+        assert_eq!(buf.push_overwrite(4), Some(0));
+        assert_eq!(buf.push_overwrite(5), Some(1));
+        assert_eq!(buf.len(), 4);
+
+        let mut output = Vec::new();
+        while let Some(value) = buf.pop() {
+            output.push(value);
+        }
+        assert_eq!(output, vec![2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_try_with_capacity() {
+        assert_eq!(RingBuffer::<u8>::try_with_capacity(0).err(), Some(AlgoError::InvalidCapacity(0)));
+        assert_eq!(RingBuffer::<u8>::try_with_capacity(6).err(), Some(AlgoError::InvalidCapacity(6)));
+        // 总大小溢出，以及分配不了这么多内存
+        let cap = usize::MAX / 2 + 1;
+        assert_eq!(RingBuffer::<u64>::try_with_capacity(cap).err(), Some(AlgoError::InvalidCapacity(cap)));
+        assert_eq!(RingBuffer::<[u8; 1 << 20]>::try_with_capacity(1 << 40).err(), Some(AlgoError::InvalidCapacity(1 << 40)));
+
+        let mut buf = RingBuffer::try_with_capacity(8).unwrap();
+        assert!(buf.push(1));
+        assert_eq!(buf.pop(), Some(1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_swap_out_of_bounds() {
+        let mut buf = RingBuffer::with_capacity(4);
+        buf.push(0);
+        buf.swap(0, 1);
+    }
+
+    #[test]
+    fn test_push_all_or_none() {
+        let (mut writer, mut reader) = ringbuffer(8);
+        for i in 0..5 {
+            writer.push(i);
+        }
+        assert_eq!(writer.free_slots(), 3);
+
+        // 空位不足时一个也不写入，并原样返回
+        assert_eq!(writer.push_all_or_none(vec![10, 11, 12, 13, 14]), Err(vec![10, 11, 12, 13, 14]));
+        assert_eq!(writer.len(), 5);
+
+        for _ in 0..2 {
+            reader.pop();
+        }
+        assert_eq!(writer.free_slots(), 5);
+        assert_eq!(writer.push_all_or_none(vec![10, 11, 12, 13, 14]), Ok(()));
+        assert_eq!(writer.free_slots(), 0);
+        assert_eq!(writer.push_all_or_none(vec![]), Ok(()));
+
+        let mut output = Vec::new();
+        while let Some(value) = reader.pop() {
+            output.push(value);
+        }
+        assert_eq!(output, vec![2, 3, 4, 10, 11, 12, 13, 14]);
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let (mut writer, mut reader) = ringbuffer(8);
+        let state = writer.snapshot();
+        assert_eq!(state, RingState { head: 0, tail: 0, len: 0, capacity: 8 });
+        assert!(state.is_empty());
+
+        for i in 0..8 {
+            writer.push(i);
+        }
+        reader.pop();
+        writer.push(8);
+        let state = reader.snapshot();
+        assert_eq!(state, RingState { head: 1, tail: 9, len: 8, capacity: 8 });
+        assert!(state.is_full());
+    }
+
+    #[test]
+    fn test_async_snapshot() {
+        let (mut writer, mut reader) = ringbuffer(16);
+        let a = thread::spawn(move || {
+            for i in 0..10000 {
+                while !writer.push(i) {
+                    thread::yield_now();
+                }
+                let state = writer.snapshot();
+                assert!(state.len <= state.capacity);
+                assert_eq!(state.len, state.tail - state.head);
+            }
+        });
+
+        let b = thread::spawn(move || {
+            let mut count = 0;
+            while count < 10000 {
+                let state = reader.snapshot();
+                assert!(state.len <= state.capacity);
+                assert_eq!(state.len, state.tail - state.head);
+                assert_eq!(state.is_full(), state.len == 16);
+                if reader.pop().is_some() {
+                    count += 1;
+                } else {
+                    thread::yield_now();
+                }
+            }
+        });
+
+        a.join().expect("Couldn't join on the associated thread");
+        b.join().expect("Couldn't join on the associated thread");
+    }
+
     #[test]
     fn test_async_ringbuffer() {
         let (mut writer, mut reader) = ringbuffer(1024);
@@ -361,6 +1091,116 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_peek_nth() {
+        let (mut writer, mut reader) = ringbuffer(4);
+        assert_eq!(reader.peek_nth(0), None);
+        writer.push(10);
+        writer.push(20);
+        writer.push(30);
+        assert_eq!(reader.peek_nth(0), Some(&10));
+        assert_eq!(reader.peek_nth(1), Some(&20));
+        assert_eq!(reader.peek_nth(2), Some(&30));
+        assert_eq!(reader.peek_nth(3), None);
+
+        // 跨越缓冲区末尾后仍按逻辑顺序
+        assert_eq!(reader.pop(), Some(10));
+        assert_eq!(reader.pop(), Some(20));
+        writer.push(40);
+        writer.push(50);
+        assert_eq!(reader.peek_nth(0), Some(&30));
+        assert_eq!(reader.peek_nth(2), Some(&50));
+        assert_eq!(reader.peek_nth(3), None);
+        assert_eq!(reader.pop(), Some(30));
+    }
+
+    #[test]
+    fn test_totals() {
+        let (mut writer, mut reader) = ringbuffer(4);
+        assert_eq!(writer.totals(), (0, 0));
+        for round in 0..10 {
+            for i in 0..3 {
+                writer.push(round * 3 + i);
+            }
+            while reader.pop().is_some() {}
+        }
+        // 已经跨越缓冲区末尾多次
+        assert_eq!(writer.totals(), (30, 30));
+        assert!(reader.totals().0 > 4);
+
+        // 满了之后 push 失败，不计入
+        writer.push_all_or_none(vec![1, 2, 3, 4]).unwrap();
+        assert!(!writer.push(5));
+        assert_eq!(reader.pop(), Some(1));
+        assert_eq!(reader.totals(), (34, 31));
+
+        let mut buf = RingBuffer::with_capacity(2);
+        buf.push_overwrite(1);
+        buf.push_overwrite(2);
+        buf.push_overwrite(3);
+        assert_eq!(buf.totals(), (3, 1));
+    }
+
+    #[test]
+    fn test_drain_until_closed() {
+        let (mut writer, mut reader) = ringbuffer(16);
+        let producer = thread::spawn(move || {
+            for i in 0..1000 {
+                while !writer.push(i) {
+                    thread::yield_now();
+                }
+            }
+            // 读取端此时多半已经在等待，关闭也要能唤醒它
+            thread::sleep(std::time::Duration::from_millis(10));
+        });
+        assert!(reader.drain_until_closed().into_iter().eq(0..1000));
+        producer.join().unwrap();
+    }
+
+    #[test]
+    fn test_drain_until_closed_after_writer_dropped() {
+        let (mut writer, mut reader) = ringbuffer(4);
+        writer.push(1);
+        writer.push(2);
+        drop(writer);
+        assert_eq!(reader.drain_until_closed(), vec![1, 2]);
+        assert_eq!(reader.drain_until_closed(), vec![]);
+    }
+
+    #[test]
+    fn test_flush_before_close() {
+        let (mut writer, mut reader) = ringbuffer(16);
+        let producer = thread::spawn(move || {
+            for i in 0..1000 {
+                while !writer.push(i) {
+                    thread::yield_now();
+                }
+            }
+            // 最后的标记值
+            while !writer.push(usize::MAX) {
+                thread::yield_now();
+            }
+            writer.flush();
+        });
+
+        let mut received = Vec::new();
+        loop {
+            // 先看到关闭，再读出剩余的元素，关闭之前的写入不能丢
+            let closed = reader.is_writer_closed();
+            while let Some(value) = reader.pop() {
+                received.push(value);
+            }
+            if closed {
+                break;
+            }
+            thread::yield_now();
+        }
+        producer.join().unwrap();
+        assert_eq!(received.pop(), Some(usize::MAX));
+        assert!(received.into_iter().eq(0..1000));
+        assert!(reader.is_writer_closed());
+    }
+
     #[test]
     fn test_drop() {
         let (mut writer, mut reader) = ringbuffer(1024);
@@ -374,4 +1214,47 @@ mod tests {
         // println!("after drop");
         assert_eq!(*A_DROPPED.lock().unwrap(), 3);
     }
+
+    // 每个测试用自己的计数，A_DROPPED 是全局的，并行测试时会互相影响
+    struct Counted(Arc<AtomicUsize>);
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_drop_wrapped() {
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let (mut writer, mut reader) = ringbuffer(8);
+        for _ in 0..8 {
+            assert!(writer.push(Counted(dropped.clone())));
+        }
+        for _ in 0..5 {
+            drop(reader.pop());
+        }
+        for _ in 0..3 {
+            assert!(writer.push(Counted(dropped.clone())));
+        }
+        // head 为 5，tail 为 11，剩余的 6 个元素跨越缓冲区末尾
+        assert_eq!(dropped.load(Ordering::Relaxed), 5);
+        drop(reader);
+        assert_eq!(dropped.load(Ordering::Relaxed), 5);
+        drop(writer);
+        assert_eq!(dropped.load(Ordering::Relaxed), 11);
+    }
+
+    #[test]
+    fn test_drop_wrapped_overwrite() {
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let mut buf = RingBuffer::with_capacity(4);
+        for _ in 0..7 {
+            drop(buf.push_overwrite(Counted(dropped.clone())));
+        }
+        // 被覆盖的 3 个已经析构，剩余 4 个从下标 3 开始绕回
+        assert_eq!(dropped.load(Ordering::Relaxed), 3);
+        drop(buf.pop());
+        drop(buf);
+        assert_eq!(dropped.load(Ordering::Relaxed), 7);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,146 @@
+//! Random number generator abstraction
+//!
+//! All randomized algorithms in this crate are generic over `Rng`.
+//! It is implemented by the built-in `prng::Xoshiro256StarStar`,
+//! and by every `rand::RngCore` with the `rand` feature.
+
+/// A minimal source of random numbers.
+pub trait Rng {
+    /// Return the next random `u64`.
+    fn next_u64(&mut self) -> u64;
+
+    /// Return a uniformly random number in `[0, bound)`, see `gen_range`.
+    ///
+    /// Panics if `bound` is 0.
+    fn gen_range(&mut self, bound: usize) -> usize {
+        gen_range(self, bound)
+    }
+
+    /// Return a uniformly random `f64` in `[0, 1)`.
+    fn gen_f64(&mut self) -> f64 {
+        // 取高 53 位作为尾数，每个值的间隔都是 2^-53
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+}
+
+/// Return a uniformly random number in `[0, bound)` without modulo bias.
+/// All randomized algorithms in this crate draw their indices from here.
+///
+/// It is Lemire's method: the high 64 bits of `x * bound` are in `[0, bound)`,
+/// and only the rare `x` whose low 64 bits fall below `2^64 % bound` are rejected,
+/// so the expensive `%` is only computed when a rejection is possible.
+///
+/// Panics if `bound` is 0.
+pub fn gen_range<R: Rng + ?Sized>(rng: &mut R, bound: usize) -> usize {
+    assert_ne!(bound, 0, "Bound must be greater than 0");
+
+    let bound = bound as u64;
+    let mut m = rng.next_u64() as u128 * bound as u128;
+    let mut low = m as u64;
+    if low < bound {
+        // 2^64 % bound，低位小于它的值会让结果不均匀，丢弃重新生成
+        let threshold = bound.wrapping_neg() % bound;
+        while low < threshold {
+            m = rng.next_u64() as u128 * bound as u128;
+            low = m as u64;
+        }
+    }
+    (m >> 64) as usize
+}
+
+#[cfg(feature = "rand")]
+impl<R: rand::RngCore> Rng for R {
+    fn next_u64(&mut self) -> u64 {
+        rand::RngCore::next_u64(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{choose::choose, knuth_shuffle::shuffle_with};
+    use crate::prng::Xoshiro256StarStar;
+    use super::{gen_range, Rng};
+
+    /// Always return the same value from `gen_range`, so the call sites are predictable.
+    struct MockRng {
+        // true 时返回 bound - 1，否则返回 0
+        max: bool,
+        calls: Vec<usize>,
+    }
+
+    impl Rng for MockRng {
+        fn next_u64(&mut self) -> u64 {
+            0
+        }
+
+        fn gen_range(&mut self, bound: usize) -> usize {
+            self.calls.push(bound);
+            if self.max { bound - 1 } else { 0 }
+        }
+    }
+
+    /// Return the given values in order.
+    struct Sequence(Vec<u64>);
+
+    impl Rng for Sequence {
+        fn next_u64(&mut self) -> u64 {
+            self.0.remove(0)
+        }
+    }
+
+    #[test]
+    fn test_gen_range() {
+        let mut rng = Sequence(vec![0, 1 << 63, u64::MAX, u64::MAX]);
+        // 0 的低位小于 2^64 % 3 == 1，被丢弃
+        assert_eq!(gen_range(&mut rng, 3), 1);
+        assert_eq!(gen_range(&mut rng, 3), 2);
+        assert_eq!(rng.gen_range(1), 0);
+
+        let mut rng = Sequence(vec![u64::MAX, 0]);
+        assert_eq!(rng.gen_f64(), 1.0 - 1.0 / (1u64 << 53) as f64);
+        assert_eq!(rng.gen_f64(), 0.0);
+    }
+
+    #[test]
+    fn test_gen_range_uniform() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        let mut counts = [0; 6];
+        for _ in 0..60000 {
+            counts[gen_range(&mut rng, 6)] += 1;
+        }
+        // 卡方检验，自由度为 5 时 99.9% 的分位数约为 20.5
+        let chi_square: f64 = counts.iter()
+            .map(|&c| (c as f64 - 10000.0).powi(2) / 10000.0)
+            .sum();
+        assert!(chi_square < 20.5, "{:?} {}", counts, chi_square);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_gen_range_zero() {
+        gen_range(&mut Xoshiro256StarStar::seed_from_u64(1), 0);
+    }
+
+    #[test]
+    fn test_mock_shuffle_with() {
+        let mut rng = MockRng { max: false, calls: Vec::new() };
+        let mut input = [1, 2, 3, 4];
+        shuffle_with(&mut input, &mut rng);
+        // 依次交换 (3, 0) (2, 0) (1, 0)
+        assert_eq!(input, [2, 3, 4, 1]);
+        assert_eq!(rng.calls, vec![4, 3, 2]);
+
+        // 每次都与自身交换，顺序不变
+        let mut rng = MockRng { max: true, calls: Vec::new() };
+        shuffle_with(&mut input, &mut rng);
+        assert_eq!(input, [2, 3, 4, 1]);
+    }
+
+    #[test]
+    fn test_mock_choose() {
+        let input = [1, 2, 3, 4];
+        let mut rng = MockRng { max: true, calls: Vec::new() };
+        assert_eq!(choose(&input, &mut rng), Some(&4));
+        assert_eq!(rng.calls, vec![4]);
+    }
+}
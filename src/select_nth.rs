@@ -0,0 +1,243 @@
+//! Quickselect algorithm
+
+use crate::{insertion_sort::insertion_sort, partition::three_way_partition, prng::with_thread_rng, rng::Rng};
+
+/// Three-way partition around the value at `pivot`, return `(lt, gt)` where
+/// `input[..lt]` is less than, `input[lt..gt]` equal to and `input[gt..]` greater than the pivot value.
+fn partition<T: Ord>(input: &mut [T], pivot: usize) -> (usize, usize) {
+    // 先把 pivot 放到最前面，再划分剩余部分
+    input.swap(0, pivot);
+    let (first, rest) = input.split_at_mut(1);
+    let (lt, gt) = three_way_partition(rest, &first[0]);
+    // pivot 与最后一个小于它的值交换，等于 pivot 的部分就连在一起了
+    input.swap(0, lt);
+    (lt, gt + 1)
+}
+
+/// Reorder the slice so that `input[k]` is the k-th smallest element (0-based),
+/// everything before it is not greater and everything after it is not less,
+/// and return a reference to it. Average O(n).
+///
+/// Panics if `k` is out of bounds.
+pub fn select_nth<T: Ord>(input: &mut [T], k: usize) -> &T {
+    let len = input.len();
+    assert!(k < len, "index out of bounds: the len is {} but the index is {}", len, k);
+
+    with_thread_rng(|rng| {
+        // 搜索区域是 [low, high)，第 k 小的值一定在区域内
+        let mut low = 0;
+        let mut high = len;
+        while high - low > 1 {
+            // 随机选取 pivot，避免有序输入退化到 O(n^2)
+            let pivot = low + rng.gen_range(high - low);
+            let (lt, gt) = partition(&mut input[low..high], pivot - low);
+
+            // 与 pivot 相等的值一次全部排除，大量重复值时不会退化到 O(n^2)
+            if k < low + lt {
+                // 搜索区域左移 [low, low+lt)
+                high = low + lt;
+            } else if k >= low + gt {
+                // 搜索区域右移 [low+gt, high)
+                low += gt;
+            } else {
+                break;
+            }
+        }
+    });
+
+    &input[k]
+}
+
+/// Return the median, the lower one for even length, or `None` if `input` is empty.
+/// It selects in a cloned buffer, so `input` is untouched. Average O(n).
+pub fn median<T: Ord + Clone>(input: &[T]) -> Option<T> {
+    if input.is_empty() {
+        return None;
+    }
+
+    let mut buf = input.to_vec();
+    // 偶数长度时 (len - 1) / 2 是较小的中位数
+    let k = (buf.len() - 1) / 2;
+    Some(select_nth(&mut buf, k).clone())
+}
+
+/// The median of the medians of groups of 5, which is guaranteed to be
+/// greater than and less than at least about 30% of the elements.
+fn median_of_medians<T: Ord + Clone>(input: &mut [T]) -> T {
+    let len = input.len();
+    let groups = len.div_ceil(5);
+    for group in 0..groups {
+        let start = group * 5;
+        let end = (start + 5).min(len);
+        insertion_sort(&mut input[start..end]);
+        // 把每组的中位数挪到最前面
+        input.swap(group, start + (end - start - 1) / 2);
+    }
+    select_deterministic(&mut input[..groups], (groups - 1) / 2).clone()
+}
+
+fn select_deterministic<T: Ord + Clone>(input: &mut [T], k: usize) -> &T {
+    // 搜索区域是 [low, high)，第 k 小的值一定在区域内
+    let mut low = 0;
+    let mut high = input.len();
+    loop {
+        if high - low <= 5 {
+            insertion_sort(&mut input[low..high]);
+            break;
+        }
+
+        let pivot = median_of_medians(&mut input[low..high]);
+        // 三路划分，大量重复值时也能保证每轮至少去掉约 30% 的元素
+        let (lt, gt) = three_way_partition(&mut input[low..high], &pivot);
+        if k < low + lt {
+            high = low + lt;
+        } else if k >= low + gt {
+            low += gt;
+        } else {
+            break;
+        }
+    }
+    &input[k]
+}
+
+/// Same as `select_nth`, but pick the pivot by median of medians, so it is O(n) in the worst case
+/// without relying on the rng. Return `None` if `k` is out of bounds.
+///
+/// The worst-case guarantee costs a larger constant factor: it is usually several times slower
+/// than `select_nth`, so prefer that one unless the input may be adversarial.
+pub fn quickselect_deterministic<T: Ord + Clone>(input: &mut [T], k: usize) -> Option<&T> {
+    if k >= input.len() {
+        return None;
+    }
+    Some(select_deterministic(input, k))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{knuth_shuffle::shuffle_with, prng::Xoshiro256StarStar, rng::Rng};
+    use super::{median, quickselect_deterministic, select_nth};
+
+    #[test]
+    fn test_select_nth() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for len in 1..50 {
+            let mut input: Vec<u32> = (0..len).map(|x| x % 7).collect();
+            let mut sorted = input.clone();
+            sorted.sort();
+            for k in 0..len as usize {
+                shuffle_with(&mut input, &mut rng);
+                assert_eq!(*select_nth(&mut input, k), sorted[k]);
+                assert!(input[..k].iter().all(|x| *x <= sorted[k]));
+                assert!(input[k + 1..].iter().all(|x| *x >= sorted[k]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_select_nth_ends() {
+        let mut input = [5, 1, 4, 2, 3];
+        assert_eq!(*select_nth(&mut input, 0), 1);
+        assert_eq!(*select_nth(&mut input, 4), 5);
+
+        let mut input: Vec<i32> = (0..1000).collect();
+        assert_eq!(*select_nth(&mut input, 0), 0);
+        assert_eq!(*select_nth(&mut input, 999), 999);
+
+        let mut input = [7];
+        assert_eq!(*select_nth(&mut input, 0), 7);
+    }
+
+    #[test]
+    fn test_select_nth_all_equal() {
+        // 二路划分时每轮只排除一个元素，这里会是 O(n^2)
+        let mut input = vec![7; 200_000];
+        assert_eq!(*select_nth(&mut input, 100_000), 7);
+        assert_eq!(median(&input), Some(7));
+
+        let mut input: Vec<u32> = (0..200_000).map(|x| x % 2).collect();
+        assert_eq!(*select_nth(&mut input, 99_999), 0);
+        assert_eq!(*select_nth(&mut input, 100_000), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_select_nth_out_of_bounds() {
+        let mut input = [1, 2, 3];
+        select_nth(&mut input, 3);
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median::<i32>(&[]), None);
+        assert_eq!(median(&[5]), Some(5));
+        assert_eq!(median(&[3, 1, 2]), Some(2));
+        assert_eq!(median(&[9, 1, 7, 3, 5]), Some(5));
+        // 偶数长度取较小的中位数
+        assert_eq!(median(&[4, 1, 3, 2]), Some(2));
+        assert_eq!(median(&[2, 1]), Some(1));
+
+        // 输入不会被修改
+        let input = vec![5, 4, 3, 2, 1];
+        assert_eq!(median(&input), Some(3));
+        assert_eq!(input, vec![5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn test_median_duplicates() {
+        assert_eq!(median(&[7; 100]), Some(7));
+        assert_eq!(median(&[1, 2, 2, 2, 9, 9]), Some(2));
+
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for len in 1..100 {
+            let mut input: Vec<u32> = (0..len).map(|x| x % 3).collect();
+            shuffle_with(&mut input, &mut rng);
+            let mut sorted = input.clone();
+            sorted.sort();
+            assert_eq!(median(&input), Some(sorted[(len as usize - 1) / 2]));
+        }
+    }
+
+    #[test]
+    fn test_quickselect_deterministic() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for len in 1..80 {
+            let input: Vec<u32> = (0..len).map(|_| rng.gen_range(20) as u32).collect();
+            let mut sorted = input.clone();
+            sorted.sort();
+            for k in 0..len as usize {
+                let mut input = input.clone();
+                assert_eq!(quickselect_deterministic(&mut input, k), Some(&sorted[k]));
+                assert!(input[..k].iter().all(|x| *x <= sorted[k]));
+                assert!(input[k + 1..].iter().all(|x| *x >= sorted[k]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_quickselect_deterministic_adversarial() {
+        let len = 10_000;
+        let inputs: Vec<Vec<i32>> = vec![
+            (0..len).collect(),
+            (0..len).rev().collect(),
+            vec![3; len as usize],
+            // 先升后降
+            (0..len / 2).chain((0..len / 2).rev()).collect(),
+            (0..len).map(|x| x % 2).collect(),
+        ];
+        for input in inputs {
+            let mut sorted = input.clone();
+            sorted.sort();
+            for &k in &[0, 1, len as usize / 2, len as usize - 1] {
+                let mut input = input.clone();
+                assert_eq!(quickselect_deterministic(&mut input, k), Some(&sorted[k]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_quickselect_deterministic_out_of_bounds() {
+        assert_eq!(quickselect_deterministic(&mut [1, 2, 3], 3), None);
+        assert_eq!(quickselect_deterministic::<i32>(&mut [], 0), None);
+        assert_eq!(quickselect_deterministic(&mut [7], 0), Some(&7));
+    }
+}
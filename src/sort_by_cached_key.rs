@@ -0,0 +1,51 @@
+//! Sort by cached key
+
+/// Sort the slice by a key that is computed only once per element.
+/// The sort is stable.
+pub fn sort_by_cached_key<T, K: Ord, F: FnMut(&T) -> K>(input: &mut [T], mut f: F) {
+    // 每个元素只调用一次 f，带上原下标，这样相同 key 的元素也能保持原顺序
+    let mut indices: Vec<(K, usize)> = input.iter()
+        .map(&mut f)
+        .enumerate()
+        .map(|(i, k)| (k, i))
+        .collect();
+    indices.sort_unstable();
+
+    // indices[i].1 是排序后第 i 个位置应放的元素的原下标
+    // 前面的交换会把元素移走，所以要沿着已交换的位置一直追到它当前所在的位置
+    for i in 0..input.len() {
+        let mut index = indices[i].1;
+        while index < i {
+            index = indices[index].1;
+        }
+        indices[i].1 = index;
+        input.swap(i, index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sort_by_cached_key;
+
+    #[test]
+    fn test_sort_by_cached_key() {
+        let mut input: [i32; 0] = [];
+        sort_by_cached_key(&mut input, |x| *x);
+
+        let mut calls = 0;
+        let mut input = ["ccc", "a", "dddd", "bb", "", "eeeee", "ff"];
+        sort_by_cached_key(&mut input, |s| {
+            calls += 1;
+            s.len()
+        });
+        assert_eq!(calls, 7);
+        assert_eq!(input, ["", "a", "bb", "ff", "ccc", "dddd", "eeeee"]);
+    }
+
+    #[test]
+    fn test_sort_by_cached_key_stable() {
+        let mut input = [(3, 'a'), (1, 'b'), (3, 'c'), (2, 'd'), (1, 'e'), (3, 'f')];
+        sort_by_cached_key(&mut input, |x| x.0);
+        assert_eq!(input, [(1, 'b'), (1, 'e'), (2, 'd'), (3, 'a'), (3, 'c'), (3, 'f')]);
+    }
+}
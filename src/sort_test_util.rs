@@ -0,0 +1,42 @@
+//! Fixtures shared by the tests of the sorting algorithms
+
+use std::{cmp::Ordering, fmt::Debug};
+
+/// Only `key` takes part in the comparison.
+#[derive(Debug, Clone)]
+pub struct Record {
+    pub key: u32,
+    pub tag: u32,
+}
+
+impl PartialEq for Record {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+impl Eq for Record {}
+impl PartialOrd for Record {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Record {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+/// Sort `input` with `sort` and compare with the standard library.
+pub fn check_sort<T: Ord + Clone + Debug>(mut input: Vec<T>, sort: impl FnOnce(&mut [T])) {
+    let mut expected = input.clone();
+    expected.sort();
+    sort(&mut input);
+    assert_eq!(input, expected);
+}
+
+/// Assert the records are sorted by key, and records with the same key keep the order of their tags.
+pub fn assert_stable(records: &[Record]) {
+    for pair in records.windows(2) {
+        assert!(pair[0].key < pair[1].key || (pair[0].key == pair[1].key && pair[0].tag < pair[1].tag));
+    }
+}
@@ -0,0 +1,109 @@
+//! Binary search over anything sorted that can be read by index
+
+/// A sorted sequence whose items are read by index, it does not need to be stored in memory.
+pub trait SortedIndexed {
+    type Item: Ord;
+
+    fn len(&self) -> usize;
+
+    /// Return the item at `index`, `index` is always less than `len()`.
+    fn get(&self, index: usize) -> Self::Item;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Ord + Clone> SortedIndexed for [T] {
+    type Item = T;
+
+    fn len(&self) -> usize {
+        <[T]>::len(self)
+    }
+
+    fn get(&self, index: usize) -> T {
+        self[index].clone()
+    }
+}
+
+/// Same as `binary_search::binary_search`, but search a `SortedIndexed`.
+pub fn binary_search_indexed<S: SortedIndexed + ?Sized>(input: &S, key: &S::Item) -> Option<usize> {
+    let len = input.len();
+    if len == 0 {
+        return None;
+    }
+
+    // 搜索区域是 [0, len-1]
+    let mut high = len - 1;
+    let mut low: usize = 0;
+
+    while low <= high {
+        // `low + (high - low) / 2` 就和 `(low + high) / 2` 的结果相同
+        // 但是有效防止了 low 和 high 太大直接相加导致溢出
+        let middle = low + (high - low) / 2;
+        let mid_value = input.get(middle);
+
+        if *key == mid_value {
+            return Some(middle);
+        } else if *key > mid_value {
+            // 搜索区域右移 [middle+1, high]
+            low = middle + 1;
+        } else {
+            // middle 为 0 时左侧已经没有值了，不能再减 1
+            if middle == 0 {
+                return None;
+            }
+            // 搜索区域左移 [low, middle-1]
+            high = middle - 1;
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{binary_search_indexed, SortedIndexed};
+
+    /// `0, 2, 4, ...` with `len` items, computed instead of stored.
+    struct Evens {
+        len: usize,
+    }
+
+    impl SortedIndexed for Evens {
+        type Item = u128;
+
+        fn len(&self) -> usize {
+            self.len
+        }
+
+        fn get(&self, index: usize) -> u128 {
+            index as u128 * 2
+        }
+    }
+
+    #[test]
+    fn test_binary_search_indexed() {
+        let input = [1, 3, 5, 7];
+        assert_eq!(binary_search_indexed(&input[..], &5), Some(2));
+        assert_eq!(binary_search_indexed(&input[..], &0), None);
+        assert_eq!(binary_search_indexed(&input[..], &8), None);
+        assert_eq!(binary_search_indexed(&[] as &[i32], &1), None);
+
+        let input = Evens { len: 10 };
+        assert_eq!(binary_search_indexed(&input, &18), Some(9));
+        assert_eq!(binary_search_indexed(&input, &7), None);
+    }
+
+    #[test]
+    fn test_binary_search_indexed_huge() {
+        // 逻辑上有 usize::MAX 个元素，(low + high) 在这里会溢出
+        let input = Evens { len: usize::MAX };
+        let half = usize::MAX / 2;
+        for index in [0, 1, half - 1, half, half + 1, half + 2, usize::MAX - 2, usize::MAX - 1] {
+            assert_eq!(binary_search_indexed(&input, &(index as u128 * 2)), Some(index));
+            assert_eq!(binary_search_indexed(&input, &(index as u128 * 2 + 1)), None);
+        }
+        assert_eq!(binary_search_indexed(&input, &(usize::MAX as u128 * 2)), None);
+    }
+}
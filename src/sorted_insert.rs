@@ -0,0 +1,39 @@
+//! Insert into a sorted `Vec`
+
+use crate::lower_bound::lower_bound;
+
+/// Insert `value` into the sorted `vec` keeping it sorted, and return the index it is inserted at.
+/// It goes before the elements equal to it.
+///
+/// Finding the position is O(log n), but the insert shifts the elements after it, so it is O(n).
+pub fn sorted_insert<T: Ord>(vec: &mut Vec<T>, value: T) -> usize {
+    let index = lower_bound(vec, &value);
+    vec.insert(index, value);
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sorted_insert;
+
+    #[test]
+    fn test_sorted_insert() {
+        let mut vec = Vec::new();
+        assert_eq!(sorted_insert(&mut vec, 5), 0);
+        // 头部，尾部，中间
+        assert_eq!(sorted_insert(&mut vec, 1), 0);
+        assert_eq!(sorted_insert(&mut vec, 9), 2);
+        assert_eq!(sorted_insert(&mut vec, 7), 2);
+        assert_eq!(vec, vec![1, 5, 7, 9]);
+    }
+
+    #[test]
+    fn test_sorted_insert_duplicates() {
+        let mut vec = vec![1, 2, 2, 3];
+        // 插入到相等元素的前面
+        assert_eq!(sorted_insert(&mut vec, 2), 1);
+        assert_eq!(sorted_insert(&mut vec, 3), 4);
+        assert_eq!(sorted_insert(&mut vec, 1), 0);
+        assert_eq!(vec, vec![1, 1, 2, 2, 2, 3, 3]);
+    }
+}
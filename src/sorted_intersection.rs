@@ -0,0 +1,76 @@
+//! Intersection of two sorted slices
+
+/// Find the first index in `input` whose value is not less than `key`.
+/// 先按 1, 2, 4, 8... 的步长向后跳，找到包含 key 的区间后再二分
+/// 结果在前面时只需 O(log i) 次比较
+fn gallop_lower_bound<T: Ord>(input: &[T], key: &T) -> usize {
+    let len = input.len();
+    let mut step = 1;
+    // 搜索区域是 [low, high)
+    let mut low = 0;
+    while step <= len && input[step - 1] < *key {
+        low = step;
+        step *= 2;
+    }
+    let mut high = step.min(len);
+
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if input[middle] < *key {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+    low
+}
+
+/// Return the sorted intersection of two sorted slices.
+/// An element appears as many times as the smaller of its counts in `a` and `b`.
+pub fn sorted_intersection<T: Ord + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    // 遍历较短的一侧，在较长的一侧中查找
+    let (small, large) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+
+    let mut result = Vec::new();
+    // large[..start] 已经被匹配或跳过，不会再被使用
+    let mut start = 0;
+    for value in small {
+        start += gallop_lower_bound(&large[start..], value);
+        if start == large.len() {
+            break;
+        }
+        if large[start] == *value {
+            result.push(value.clone());
+            start += 1;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sorted_intersection;
+
+    #[test]
+    fn test_sorted_intersection() {
+        assert_eq!(sorted_intersection(&[1, 2, 3, 4], &[2, 4, 6]), vec![2, 4]);
+        assert_eq!(sorted_intersection(&[2, 4, 6], &[1, 2, 3, 4]), vec![2, 4]);
+        assert_eq!(sorted_intersection(&[1, 3, 5], &[2, 4, 6]), Vec::<i32>::new());
+        assert_eq!(sorted_intersection(&[], &[2, 4, 6]), Vec::<i32>::new());
+        assert_eq!(sorted_intersection(&[7], &[0, 1, 2, 3, 4, 5, 6, 7]), vec![7]);
+    }
+
+    #[test]
+    fn test_sorted_intersection_multiplicity() {
+        assert_eq!(sorted_intersection(&[1, 2, 2, 2, 3], &[2, 2]), vec![2, 2]);
+        assert_eq!(sorted_intersection(&[2], &[1, 2, 2, 2, 3]), vec![2]);
+        assert_eq!(sorted_intersection(&[1, 1, 2, 3, 3], &[1, 3, 3, 3, 4, 5]), vec![1, 3, 3]);
+    }
+
+    #[test]
+    fn test_sorted_intersection_large() {
+        let large: Vec<i32> = (0..1000).map(|x| x * 2).collect();
+        let small = [-1, 0, 3, 500, 1998, 1999, 5000];
+        assert_eq!(sorted_intersection(&small, &large), vec![0, 500, 1998]);
+    }
+}
@@ -0,0 +1,196 @@
+//! Sorted map backed by a `Vec`
+
+use std::ops::{Bound, RangeBounds};
+use crate::{binary_search_by::binary_search_by, lower_bound::partition_point};
+
+/// A map that keeps its entries sorted by key in a `Vec<(K, V)>`.
+/// Lookups are O(log n), inserts are O(n) because of shifting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortedVecMap<K: Ord, V> {
+    entries: Vec<(K, V)>,
+}
+
+impl<K: Ord, V> Default for SortedVecMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> SortedVecMap<K, V> {
+    pub fn new() -> Self {
+        Self { entries: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Find the index of `key`, or the index to insert it at.
+    fn search(&self, key: &K) -> Result<usize, usize> {
+        binary_search_by(&self.entries, |(k, _)| k.cmp(key))
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.search(key).ok().map(|index| &self.entries[index].1)
+    }
+
+    /// Insert a value, return the old value if the key exists.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.search(&key) {
+            Ok(index) => Some(std::mem::replace(&mut self.entries[index].1, value)),
+            Err(index) => {
+                self.entries.insert(index, (key, value));
+                None
+            }
+        }
+    }
+
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        match self.search(&key) {
+            Ok(index) => Entry::Occupied(OccupiedEntry { map: self, index }),
+            Err(index) => Entry::Vacant(VacantEntry { map: self, index, key }),
+        }
+    }
+
+    /// Iterate the entries whose keys are within `bounds`, in key order.
+    /// The start and end are both found by binary search.
+    pub fn range<R: RangeBounds<K>>(&self, bounds: R) -> impl Iterator<Item = (&K, &V)> {
+        // start 是第一个在范围内的下标，end 是第一个超出范围的下标
+        let start = match bounds.start_bound() {
+            Bound::Included(key) => partition_point(&self.entries, |(k, _)| k < key),
+            Bound::Excluded(key) => partition_point(&self.entries, |(k, _)| k <= key),
+            Bound::Unbounded => 0,
+        };
+        let end = match bounds.end_bound() {
+            Bound::Included(key) => partition_point(&self.entries, |(k, _)| k <= key),
+            Bound::Excluded(key) => partition_point(&self.entries, |(k, _)| k < key),
+            Bound::Unbounded => self.entries.len(),
+        };
+
+        // 范围的起点大于终点时为空
+        self.entries[start..end.max(start)].iter().map(|(k, v)| (k, v))
+    }
+
+    /// Iterate the entries in key order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+}
+
+/// A view into a single entry of `SortedVecMap`, which may be vacant or occupied.
+pub enum Entry<'a, K: Ord, V> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+pub struct OccupiedEntry<'a, K: Ord, V> {
+    map: &'a mut SortedVecMap<K, V>,
+    index: usize,
+}
+
+pub struct VacantEntry<'a, K: Ord, V> {
+    map: &'a mut SortedVecMap<K, V>,
+    // 插入后仍保持有序的位置
+    index: usize,
+    key: K,
+}
+
+impl<'a, K: Ord, V> Entry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => &entry.map.entries[entry.index].0,
+            Entry::Vacant(entry) => &entry.key,
+        }
+    }
+
+    /// Insert `default` if the entry is vacant, and return a mutable reference to the value.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// Insert the result of `f` if the entry is vacant, and return a mutable reference to the value.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, f: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => &mut entry.map.entries[entry.index].1,
+            Entry::Vacant(entry) => {
+                entry.map.entries.insert(entry.index, (entry.key, f()));
+                &mut entry.map.entries[entry.index].1
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+    use super::SortedVecMap;
+
+    #[test]
+    fn test_insert() {
+        let mut map = SortedVecMap::new();
+        assert!(map.is_empty());
+        assert_eq!(map.insert(5, "5"), None);
+        assert_eq!(map.insert(1, "1"), None);
+        assert_eq!(map.insert(3, "3"), None);
+        assert_eq!(map.insert(7, "7"), None);
+        assert_eq!(map.len(), 4);
+
+        assert_eq!(map.get(&3), Some(&"3"));
+        assert_eq!(map.get(&4), None);
+        let keys: Vec<i32> = map.iter().map(|(k, _)| *k).collect();
+        assert_eq!(keys, vec![1, 3, 5, 7]);
+    }
+
+    #[test]
+    fn test_overwrite() {
+        let mut map = SortedVecMap::new();
+        map.insert(1, "a");
+        map.insert(2, "b");
+        assert_eq!(map.insert(1, "c"), Some("a"));
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&1), Some(&"c"));
+    }
+
+    fn keys<'a>(iter: impl Iterator<Item = (&'a i32, &'a i32)>) -> Vec<i32> {
+        iter.map(|(k, _)| *k).collect()
+    }
+
+    #[test]
+    fn test_range() {
+        let mut map = SortedVecMap::new();
+        for key in [9, 3, 7, 1, 5] {
+            map.insert(key, key * 10);
+        }
+
+        assert_eq!(keys(map.range(3..=7)), vec![3, 5, 7]);
+        assert_eq!(keys(map.range(..4)), vec![1, 3]);
+        assert_eq!(keys(map.range(3..7)), vec![3, 5]);
+        assert_eq!(keys(map.range(4..)), vec![5, 7, 9]);
+        assert_eq!(keys(map.range(..)), vec![1, 3, 5, 7, 9]);
+        assert_eq!(keys(map.range(10..)), Vec::<i32>::new());
+        assert_eq!(keys(map.range(4..5)), Vec::<i32>::new());
+        assert_eq!(keys(map.range((Bound::Excluded(3), Bound::Included(9)))), vec![5, 7, 9]);
+        assert_eq!(keys(map.range((Bound::Excluded(5), Bound::Excluded(5)))), Vec::<i32>::new());
+
+        let values: Vec<i32> = map.range(3..=7).map(|(_, v)| *v).collect();
+        assert_eq!(values, vec![30, 50, 70]);
+    }
+
+    #[test]
+    fn test_entry() {
+        let mut map = SortedVecMap::new();
+        for word in ["b", "a", "c", "a", "b", "a"] {
+            *map.entry(word).or_insert(0) += 1;
+        }
+        let counts: Vec<(&str, i32)> = map.iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(counts, vec![("a", 3), ("b", 2), ("c", 1)]);
+
+        assert_eq!(map.entry("d").key(), &"d");
+        assert_eq!(*map.entry("d").or_insert_with(|| 10), 10);
+        assert_eq!(*map.entry("a").or_insert_with(|| 10), 3);
+    }
+}
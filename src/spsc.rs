@@ -0,0 +1,104 @@
+//! Single-producer single-consumer channels on top of the ring buffer
+
+use crate::ringbuffer::{ringbuffer, RingbufferReader, RingbufferWriter};
+
+/// Build a channel whose sender publishes elements in batches, `capacity` must be a power of two.
+/// The receiving half is a plain `RingbufferReader`.
+pub fn batched<T>(capacity: usize) -> (BatchedSender<T>, RingbufferReader<T>) {
+    let (writer, reader) = ringbuffer(capacity);
+    (BatchedSender { writer, pending: 0 }, reader)
+}
+
+/// Sender that writes elements into the buffer right away, but only moves tail on `flush`.
+///
+/// Latency and throughput: a push publishes nothing, so the reader sees no element until `flush`,
+/// and the unflushed elements still take room in the buffer. In exchange a batch of n elements
+/// costs one atomic update of tail and at most one reader wakeup instead of n.
+/// Flush as soon as the reader needs the data, dropping the sender flushes too.
+pub struct BatchedSender<T> {
+    writer: RingbufferWriter<T>,
+    // 已经写入但还没有发布的元素个数
+    pending: usize,
+}
+
+impl<T> BatchedSender<T> {
+    /// Write the element after the unflushed ones, it is not visible to the reader until `flush`.
+    /// Return `false` if the buffer is full, counting the unflushed elements.
+    pub fn push(&mut self, value: T) -> bool {
+        let written = self.writer.write_unpublished(self.pending, value);
+        if written {
+            self.pending += 1;
+        }
+        written
+    }
+
+    /// Publish the unflushed elements to the reader at once.
+    pub fn flush(&mut self) {
+        self.writer.publish(self.pending);
+        self.pending = 0;
+    }
+
+    /// Number of elements pushed since the last `flush`.
+    pub fn pending(&self) -> usize {
+        self.pending
+    }
+}
+
+impl<T> Drop for BatchedSender<T> {
+    fn drop(&mut self) {
+        // 发布之后由读取端或缓冲区的释放负责析构，未发布的元素不会泄漏
+        self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use super::batched;
+
+    #[test]
+    fn test_batched_visible_after_flush() {
+        let (mut sender, mut reader) = batched(4);
+        assert!(sender.push(1));
+        assert!(sender.push(2));
+        assert_eq!(sender.pending(), 2);
+        // 没有 flush 之前读取端看不到
+        assert!(reader.is_empty());
+        assert_eq!(reader.pop(), None);
+
+        sender.flush();
+        assert_eq!(sender.pending(), 0);
+        assert_eq!(reader.len(), 2);
+        assert_eq!(reader.pop(), Some(1));
+
+        // 未发布的元素也占用空间，剩余 3 个空位
+        assert!(sender.push(3));
+        assert!(sender.push(4));
+        assert!(sender.push(5));
+        assert!(!sender.push(6));
+        assert_eq!(reader.pop(), Some(2));
+        assert_eq!(reader.pop(), None);
+        // 析构时发布剩余的元素
+        drop(sender);
+        assert_eq!(reader.drain_until_closed(), vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn test_batched_across_threads() {
+        let (mut sender, mut reader) = batched(64);
+        let producer = thread::spawn(move || {
+            for batch in 0..100 {
+                for i in 0..10 {
+                    while !sender.push(batch * 10 + i) {
+                        // 满了先把已写入的发布出去，让读取端腾出空间
+                        sender.flush();
+                        thread::yield_now();
+                    }
+                }
+                sender.flush();
+            }
+        });
+        assert!(reader.drain_until_closed().into_iter().eq(0..1000));
+        producer.join().unwrap();
+    }
+}
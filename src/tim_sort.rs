@@ -0,0 +1,271 @@
+//! Timsort algorithm
+
+use crate::lower_bound::partition_point;
+
+/// Inputs shorter than this are sorted by a single insertion sort.
+const MIN_MERGE: usize = 32;
+/// Switch to galloping after one side wins this many times in a row.
+const MIN_GALLOP: usize = 7;
+
+/// Compute the minimum run length for `len` elements,
+/// so that `len / min_run` is a power of two or slightly less.
+fn min_run_length(mut len: usize) -> usize {
+    // 如果移出的低位中有 1，结果加 1
+    let mut extra = 0;
+    while len >= MIN_MERGE {
+        extra |= len & 1;
+        len >>= 1;
+    }
+    len + extra
+}
+
+/// Return the length of the natural run at the start of `input`,
+/// a strictly descending run is reversed in place.
+fn count_run<T: Ord>(input: &mut [T]) -> usize {
+    let len = input.len();
+    if len <= 1 {
+        return len;
+    }
+
+    let mut end = 2;
+    if input[1] < input[0] {
+        // 只有严格递减的段才能反转，否则会破坏稳定性
+        while end < len && input[end] < input[end - 1] {
+            end += 1;
+        }
+        input[..end].reverse();
+    } else {
+        while end < len && input[end] >= input[end - 1] {
+            end += 1;
+        }
+    }
+    end
+}
+
+/// Stable binary insertion sort, `input[..sorted]` is already sorted.
+fn insertion_sort<T: Ord>(input: &mut [T], sorted: usize) {
+    for i in sorted.max(1)..input.len() {
+        // 插入到相等元素的后面，保持稳定
+        let position = partition_point(&input[..i], |x| x <= &input[i]);
+        input[position..=i].rotate_right(1);
+    }
+}
+
+/// Same as `partition_point`, but search from the start with exponentially growing steps.
+/// It is faster when the result is near the start.
+fn gallop<T, P: FnMut(&T) -> bool>(input: &[T], mut pred: P) -> usize {
+    // 依次检查下标 0, 1, 3, 7, ...，直到 pred 为 false 或越界
+    let mut bound = 1;
+    while bound <= input.len() && pred(&input[bound - 1]) {
+        bound *= 2;
+    }
+    // 此时前 bound / 2 个元素都满足 pred
+    let low = bound / 2;
+    let high = bound.min(input.len());
+    low + partition_point(&input[low..high], pred)
+}
+
+/// Merge the sorted runs `input[..middle]` and `input[middle..]`.
+/// `buf` is used as the scratch space of the left run.
+fn merge<T: Ord + Clone>(input: &mut [T], middle: usize, buf: &mut Vec<T>) {
+    // 左侧中不大于右侧第一个元素的前缀已经在最终位置
+    let start = gallop(&input[..middle], |x| x <= &input[middle]);
+    if start == middle {
+        return;
+    }
+    // 右侧中不小于左侧最后一个元素的后缀也已经在最终位置
+    let end = middle + gallop(&input[middle..], |x| x < &input[middle - 1]);
+    let input = &mut input[start..end];
+    let middle = middle - start;
+
+    buf.clear();
+    buf.extend_from_slice(&input[..middle]);
+    let left = &buf[..];
+    let len = input.len();
+
+    // i 是 left 中的下标，j 是右侧在 input 中的下标，k 是写入的位置
+    // 始终有 k <= j，所以写入不会覆盖右侧还未合并的元素
+    let mut i = 0;
+    let mut j = middle;
+    let mut k = 0;
+    let mut left_wins = 0;
+    let mut right_wins = 0;
+    while i < left.len() && j < len {
+        if left_wins >= MIN_GALLOP {
+            // 一次复制左侧所有不大于 input[j] 的元素
+            let count = gallop(&left[i..], |x| x <= &input[j]);
+            input[k..k + count].clone_from_slice(&left[i..i + count]);
+            i += count;
+            k += count;
+            left_wins = 0;
+        } else if right_wins >= MIN_GALLOP {
+            // 一次复制右侧所有小于 left[i] 的元素
+            let count = gallop(&input[j..], |x| x < &left[i]);
+            for offset in 0..count {
+                let value = input[j + offset].clone();
+                input[k + offset] = value;
+            }
+            j += count;
+            k += count;
+            right_wins = 0;
+        } else if left[i] <= input[j] {
+            // 相等时取左侧，保持稳定
+            input[k].clone_from(&left[i]);
+            i += 1;
+            k += 1;
+            left_wins += 1;
+            right_wins = 0;
+        } else {
+            let value = input[j].clone();
+            input[k] = value;
+            j += 1;
+            k += 1;
+            right_wins += 1;
+            left_wins = 0;
+        }
+    }
+
+    // 右侧剩余的元素已经在最终位置，只需复制左侧剩余的元素
+    input[k..k + left.len() - i].clone_from_slice(&left[i..]);
+}
+
+/// Sort the slice with Timsort.
+/// It finds the natural runs and merges them, so partially sorted input is fast. Stable.
+pub fn tim_sort<T: Ord + Clone>(input: &mut [T]) {
+    let len = input.len();
+    if len < MIN_MERGE {
+        let sorted = count_run(input);
+        insertion_sort(input, sorted);
+        return;
+    }
+
+    let min_run = min_run_length(len);
+    // 栈上每个 run 是 (start, len)
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    let mut buf = Vec::new();
+    let mut start = 0;
+    while start < len {
+        // 找到自然有序的段，太短时用插入排序扩展到 min_run
+        let mut run_len = count_run(&mut input[start..]);
+        if run_len < min_run {
+            let forced = min_run.min(len - start);
+            insertion_sort(&mut input[start..start + forced], run_len);
+            run_len = forced;
+        }
+        runs.push((start, run_len));
+        start += run_len;
+
+        merge_collapse(input, &mut runs, &mut buf);
+    }
+
+    // 合并栈上剩余的 run
+    while runs.len() > 1 {
+        let n = runs.len();
+        merge_at(input, &mut runs, n - 2, &mut buf);
+    }
+}
+
+/// Merge the runs on the stack until the invariants hold:
+/// every run is longer than the sum of the next two, and longer than the next one.
+fn merge_collapse<T: Ord + Clone>(input: &mut [T], runs: &mut Vec<(usize, usize)>, buf: &mut Vec<T>) {
+    while runs.len() > 1 {
+        let n = runs.len();
+        let len_at = |i: usize| runs[i].1;
+        if (n >= 3 && len_at(n - 3) <= len_at(n - 2) + len_at(n - 1))
+            || (n >= 4 && len_at(n - 4) <= len_at(n - 3) + len_at(n - 2))
+        {
+            // 与较短的一侧合并
+            if len_at(n - 3) < len_at(n - 1) {
+                merge_at(input, runs, n - 3, buf);
+            } else {
+                merge_at(input, runs, n - 2, buf);
+            }
+        } else if len_at(n - 2) <= len_at(n - 1) {
+            merge_at(input, runs, n - 2, buf);
+        } else {
+            break;
+        }
+    }
+}
+
+/// Merge `runs[i]` and `runs[i + 1]`.
+fn merge_at<T: Ord + Clone>(input: &mut [T], runs: &mut Vec<(usize, usize)>, i: usize, buf: &mut Vec<T>) {
+    let (start, left_len) = runs[i];
+    let (_, right_len) = runs[i + 1];
+    merge(&mut input[start..start + left_len + right_len], left_len, buf);
+    runs[i] = (start, left_len + right_len);
+    runs.remove(i + 1);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{prng::Xoshiro256StarStar, rng::Rng, sort_test_util::{assert_stable, check_sort, Record}};
+    use super::{min_run_length, tim_sort};
+
+    fn check(input: Vec<i32>) {
+        check_sort(input, tim_sort);
+    }
+
+    #[test]
+    fn test_min_run_length() {
+        assert_eq!(min_run_length(31), 31);
+        assert_eq!(min_run_length(64), 16);
+        assert_eq!(min_run_length(65), 17);
+        assert_eq!(min_run_length(1000), 32);
+    }
+
+    #[test]
+    fn test_tim_sort_random() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for len in (0..200).chain([1000, 5000]) {
+            check((0..len).map(|_| rng.gen_range(100) as i32).collect());
+        }
+    }
+
+    #[test]
+    fn test_tim_sort_sorted() {
+        check((0..5000).collect());
+        check((0..5000).rev().collect());
+        check(vec![3; 1000]);
+    }
+
+    #[test]
+    fn test_tim_sort_runs() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2);
+        // 若干长度随机的升序段和降序段拼接在一起
+        let mut input = Vec::new();
+        while input.len() < 10000 {
+            let run_len = 1 + rng.gen_range(500);
+            let base = rng.gen_range(10000) as i32;
+            if rng.gen_range(2) == 0 {
+                input.extend(base..base + run_len as i32);
+            } else {
+                input.extend((base..base + run_len as i32).rev());
+            }
+        }
+        check(input);
+
+        // 有序的日志后面追加少量乱序的元素
+        let mut input: Vec<i32> = (0..5000).collect();
+        input.extend((0..50).map(|_| rng.gen_range(5000) as i32));
+        check(input);
+    }
+
+    #[test]
+    fn test_tim_sort_stable() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(3);
+        for len in [0, 1, 2, 31, 32, 100, 1000, 5000] {
+            let mut input: Vec<Record> = (0..len)
+                .map(|tag| Record { key: rng.gen_range(10) as u32, tag })
+                .collect();
+            tim_sort(&mut input);
+            // key 相同的记录保持输入时 tag 的顺序
+            assert_stable(&input);
+        }
+
+        // 降序段中的相等元素不能被反转
+        let mut input: Vec<Record> = (0..1000).map(|tag| Record { key: (1000 - tag) / 4, tag }).collect();
+        tim_sort(&mut input);
+        assert_stable(&input);
+    }
+}
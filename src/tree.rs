@@ -0,0 +1,510 @@
+//! Tree with any number of children per node
+
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    fmt::{Display, Write},
+    rc::{Rc, Weak},
+};
+use crate::error::ParseError;
+
+/// A tree node, every node is shared by `Rc` so it can be kept as a handle.
+/// 子节点列表放在 RefCell 中，所以通过共享的句柄也能添加子节点
+/// 父节点用 Weak 指向，避免父子之间的引用环导致内存泄漏
+#[derive(Debug)]
+pub struct TreeNode<T> {
+    value: T,
+    parent: Weak<TreeNode<T>>,
+    children: RefCell<Vec<Rc<TreeNode<T>>>>,
+}
+
+impl<T> TreeNode<T> {
+    /// Build a tree with a single root node.
+    pub fn new(value: T) -> Rc<Self> {
+        Rc::new(Self {
+            value,
+            parent: Weak::new(),
+            children: RefCell::new(Vec::new()),
+        })
+    }
+
+    /// Append a child with `value` after the existing children, and return it.
+    /// The child keeps a weak link back to `self`, see [`TreeNode::parent`].
+    pub fn add_child(self: &Rc<Self>, value: T) -> Rc<TreeNode<T>> {
+        let child = Rc::new(Self {
+            value,
+            parent: Rc::downgrade(self),
+            children: RefCell::new(Vec::new()),
+        });
+        self.children.borrow_mut().push(Rc::clone(&child));
+        child
+    }
+
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// The node this one was added to, `None` for the root.
+    /// Also `None` once the parent has been dropped, when this node is still held by a handle.
+    pub fn parent(&self) -> Option<Rc<TreeNode<T>>> {
+        self.parent.upgrade()
+    }
+
+    /// The children from the first added to the last added.
+    pub fn children(&self) -> Vec<Rc<TreeNode<T>>> {
+        self.children.borrow().clone()
+    }
+
+    pub fn is_leaf(&self) -> bool {
+        self.children.borrow().is_empty()
+    }
+
+    /// The number of edges on the longest path from this node down to a leaf, 0 for a leaf.
+    pub fn height(&self) -> usize {
+        let mut height = 0;
+        // 栈中保存子节点和它到当前节点的边数，避免递归
+        let mut stack: Vec<(Rc<TreeNode<T>>, usize)> =
+            self.children.borrow().iter().map(|child| (Rc::clone(child), 1)).collect();
+        while let Some((node, depth)) = stack.pop() {
+            height = height.max(depth);
+            stack.extend(node.children.borrow().iter().map(|child| (Rc::clone(child), depth + 1)));
+        }
+        height
+    }
+
+    /// The number of nodes in this subtree, including this node.
+    pub fn size(&self) -> usize {
+        let mut size = 1;
+        let mut stack = self.children();
+        while let Some(node) = stack.pop() {
+            size += 1;
+            stack.extend(node.children.borrow().iter().cloned());
+        }
+        size
+    }
+
+    /// Build a new tree of the same shape, with every value transformed by `f`.
+    pub fn map<U, F: Fn(&T) -> U>(&self, f: F) -> Rc<TreeNode<U>> {
+        let root = TreeNode::new(f(&self.value));
+        // 栈中保存原树中待复制的子节点，以及新树中对应的父节点
+        let mut stack = vec![(self.children(), Rc::clone(&root))];
+        while let Some((children, parent)) = stack.pop() {
+            for child in children {
+                let mapped = parent.add_child(f(&child.value));
+                stack.push((child.children(), mapped));
+            }
+        }
+        root
+    }
+
+    /// Visit the node, then each child subtree from left to right, and return the nodes in that order.
+    /// The values live behind the `RefCell` of their parents, so the nodes are returned instead of `&T`.
+    /// It uses an explicit stack, so deep trees do not overflow the call stack.
+    pub fn dfs_preorder(self: &Rc<Self>) -> Vec<Rc<TreeNode<T>>> {
+        let mut result = Vec::new();
+        let mut stack = vec![Rc::clone(self)];
+        while let Some(node) = stack.pop() {
+            // 倒序入栈，保证最左侧的子节点最先出栈
+            stack.extend(node.children.borrow().iter().rev().cloned());
+            result.push(node);
+        }
+        result
+    }
+
+    /// Return the first node whose value satisfies `pred`, searching in the order of
+    /// [`TreeNode::dfs_preorder`]: the node itself, then each child subtree from left to right.
+    pub fn find<P: Fn(&T) -> bool>(self: &Rc<Self>, pred: P) -> Option<Rc<TreeNode<T>>> {
+        let mut stack = vec![Rc::clone(self)];
+        while let Some(node) = stack.pop() {
+            if pred(&node.value) {
+                return Some(node);
+            }
+            stack.extend(node.children.borrow().iter().rev().cloned());
+        }
+        None
+    }
+
+    /// The number of nodes in this subtree whose value satisfies `pred`.
+    pub fn count_matching<P: Fn(&T) -> bool>(self: &Rc<Self>, pred: P) -> usize {
+        self.dfs_preorder().iter().filter(|node| pred(&node.value)).count()
+    }
+
+    /// Visit the nodes level by level, each level from left to right, and return them in that order.
+    /// So every node comes after all the nodes that are fewer edges away from `self`.
+    pub fn bfs(self: &Rc<Self>) -> Vec<Rc<TreeNode<T>>> {
+        let mut result = Vec::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(Rc::clone(self));
+        while let Some(node) = queue.pop_front() {
+            queue.extend(node.children.borrow().iter().cloned());
+            result.push(node);
+        }
+        result
+    }
+}
+
+impl<T: Display> TreeNode<T> {
+    /// Write the tree as the value followed by each child subtree in parentheses,
+    /// e.g. `1(2(4)(5))(3)`. It can be parsed back by [`TreeNode::from_paren_string`].
+    pub fn to_paren_string(&self) -> String {
+        let mut result = self.value.to_string();
+        // None 表示写出一个右括号，Some 表示写出一个子树的开头
+        let mut stack: Vec<Option<Rc<TreeNode<T>>>> = Vec::new();
+        for child in self.children.borrow().iter().rev() {
+            stack.push(None);
+            stack.push(Some(Rc::clone(child)));
+        }
+        while let Some(step) = stack.pop() {
+            match step {
+                Some(node) => {
+                    write!(result, "({}", node.value).unwrap();
+                    for child in node.children.borrow().iter().rev() {
+                        stack.push(None);
+                        stack.push(Some(Rc::clone(child)));
+                    }
+                }
+                None => result.push(')'),
+            }
+        }
+        result
+    }
+}
+
+impl TreeNode<i32> {
+    /// Parse the format written by [`TreeNode::to_paren_string`], e.g. `1(2(4)(5))(3)`.
+    /// No whitespace is allowed.
+    pub fn from_paren_string(s: &str) -> Result<Rc<Self>, ParseError> {
+        let bytes = s.as_bytes();
+        let mut position = 0;
+        let root = TreeNode::new(parse_i32(s, &mut position)?);
+        // 栈顶是当前正在添加子节点的节点
+        let mut stack = vec![Rc::clone(&root)];
+        while position < bytes.len() {
+            match bytes[position] {
+                b'(' => {
+                    position += 1;
+                    let value = parse_i32(s, &mut position)?;
+                    let child = stack.last().unwrap().add_child(value);
+                    stack.push(child);
+                }
+                // 根节点没有对应的左括号
+                b')' if stack.len() > 1 => {
+                    position += 1;
+                    stack.pop();
+                }
+                _ => return Err(unexpected_char(s, position)),
+            }
+        }
+        if stack.len() > 1 {
+            return Err(ParseError::UnexpectedEnd);
+        }
+        Ok(root)
+    }
+}
+
+/// Parse an `i32` starting at `*position`, and move `*position` after it.
+fn parse_i32(s: &str, position: &mut usize) -> Result<i32, ParseError> {
+    let bytes = s.as_bytes();
+    let start = *position;
+    let mut end = start;
+    if bytes.get(end) == Some(&b'-') {
+        end += 1;
+    }
+    let digits = end;
+    while bytes.get(end).is_some_and(u8::is_ascii_digit) {
+        end += 1;
+    }
+    if end == digits {
+        return Err(if end == bytes.len() { ParseError::UnexpectedEnd } else { unexpected_char(s, end) });
+    }
+    *position = end;
+    s[start..end].parse().map_err(|_| ParseError::InvalidNumber(start))
+}
+
+fn unexpected_char(s: &str, position: usize) -> ParseError {
+    ParseError::UnexpectedChar(position, s[position..].chars().next().unwrap())
+}
+
+impl<T> Drop for TreeNode<T> {
+    fn drop(&mut self) {
+        // 默认的释放是递归的，很深的树会栈溢出，所以改为用显式栈释放
+        // 只拆开没有其他句柄的子节点，仍被外部持有的子树交给持有者释放
+        let mut stack = std::mem::take(self.children.get_mut());
+        while let Some(child) = stack.pop() {
+            if let Ok(mut node) = Rc::try_unwrap(child) {
+                stack.append(node.children.get_mut());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+    use crate::error::ParseError;
+    use super::TreeNode;
+
+    fn values(nodes: &[Rc<TreeNode<i32>>]) -> Vec<i32> {
+        nodes.iter().map(|node| *node.value()).collect()
+    }
+
+    /// ```text
+    ///       1
+    ///     / | \
+    ///    2  3  4
+    ///   / \     \
+    ///  5   6     7
+    ///            |
+    ///            8
+    /// ```
+    fn sample() -> Rc<TreeNode<i32>> {
+        let root = TreeNode::new(1);
+        let two = root.add_child(2);
+        root.add_child(3);
+        let four = root.add_child(4);
+        two.add_child(5);
+        two.add_child(6);
+        four.add_child(7).add_child(8);
+        root
+    }
+
+    #[test]
+    fn test_tree() {
+        //     1
+        //    / \
+        //   2   3
+        //   |
+        //   4
+        let root = TreeNode::new(1);
+        let two = root.add_child(2);
+        root.add_child(3);
+        two.add_child(4);
+
+        assert_eq!(*root.value(), 1);
+        let children = root.children();
+        assert_eq!(children.iter().map(|c| *c.value()).collect::<Vec<_>>(), vec![2, 3]);
+        assert!(!children[0].is_leaf());
+        assert!(children[1].is_leaf());
+        assert_eq!(*children[0].children()[0].value(), 4);
+    }
+
+    #[test]
+    fn test_single_node() {
+        let root = TreeNode::new("root");
+        assert_eq!(*root.value(), "root");
+        assert!(root.is_leaf());
+        assert!(root.children().is_empty());
+    }
+
+    #[test]
+    fn test_parent() {
+        let root = sample();
+        assert!(root.parent().is_none());
+
+        let two = &root.children()[0];
+        assert!(Rc::ptr_eq(&two.parent().unwrap(), &root));
+        let six = &two.children()[1];
+        assert_eq!(*six.value(), 6);
+        assert!(Rc::ptr_eq(&six.parent().unwrap(), two));
+
+        // 沿父节点一路走到根
+        let eight = &root.children()[2].children()[0].children()[0];
+        let mut path = vec![*eight.value()];
+        let mut node = Rc::clone(eight);
+        while let Some(parent) = node.parent() {
+            path.push(*parent.value());
+            node = parent;
+        }
+        assert_eq!(path, vec![8, 7, 4, 1]);
+    }
+
+    #[test]
+    fn test_height_and_size() {
+        let root = sample();
+        assert_eq!(root.height(), 3);
+        assert_eq!(root.size(), 8);
+        assert_eq!(root.children()[0].height(), 1);
+        assert_eq!(root.children()[0].size(), 3);
+
+        // 满二叉树
+        let root = TreeNode::new(0);
+        for child in [root.add_child(1), root.add_child(2)] {
+            child.add_child(3);
+            child.add_child(4);
+        }
+        assert_eq!(root.height(), 2);
+        assert_eq!(root.size(), 7);
+
+        let root = TreeNode::new(0);
+        assert_eq!(root.height(), 0);
+        assert_eq!(root.size(), 1);
+    }
+
+    #[test]
+    fn test_height_and_size_deep() {
+        // 退化成链表的树
+        let root = TreeNode::new(0);
+        let mut node = Rc::clone(&root);
+        for i in 1..100_000 {
+            node = node.add_child(i);
+        }
+        assert_eq!(root.height(), 99_999);
+        assert_eq!(root.size(), 100_000);
+        assert_eq!(node.height(), 0);
+    }
+
+    #[test]
+    fn test_map() {
+        let root = sample();
+        let mapped = root.map(|v| format!("#{}", v));
+        let expected: Vec<String> = values(&root.dfs_preorder()).iter().map(|v| format!("#{}", v)).collect();
+        let actual: Vec<String> = mapped.dfs_preorder().iter().map(|node| node.value().clone()).collect();
+        assert_eq!(actual, expected);
+        let actual: Vec<String> = mapped.bfs().iter().map(|node| node.value().clone()).collect();
+        assert_eq!(actual, vec!["#1", "#2", "#3", "#4", "#5", "#6", "#7", "#8"]);
+
+        // 每个节点的子节点数不变，父节点也指向新树
+        for (original, mapped) in root.dfs_preorder().iter().zip(mapped.dfs_preorder()) {
+            assert_eq!(original.children().len(), mapped.children().len());
+            assert_eq!(original.parent().map(|p| format!("#{}", p.value())), mapped.parent().map(|p| p.value().clone()));
+        }
+
+        let single = TreeNode::new(7).map(|v| v * 2);
+        assert_eq!(*single.value(), 14);
+        assert!(single.is_leaf());
+    }
+
+    #[test]
+    fn test_paren_string() {
+        let root = sample();
+        assert_eq!(root.to_paren_string(), "1(2(5)(6))(3)(4(7(8)))");
+        let parsed = TreeNode::from_paren_string(&root.to_paren_string()).unwrap();
+        assert_eq!(values(&parsed.dfs_preorder()), values(&root.dfs_preorder()));
+        assert_eq!(values(&parsed.bfs()), values(&root.bfs()));
+        assert_eq!(parsed.to_paren_string(), root.to_paren_string());
+
+        let parsed = TreeNode::from_paren_string("-1(20(-4)(5))(3)").unwrap();
+        assert_eq!(values(&parsed.dfs_preorder()), vec![-1, 20, -4, 5, 3]);
+        assert_eq!(parsed.children().len(), 2);
+        assert_eq!(TreeNode::from_paren_string("7").unwrap().to_paren_string(), "7");
+        assert_eq!(TreeNode::new("a").to_paren_string(), "a");
+    }
+
+    #[test]
+    fn test_from_paren_string_malformed() {
+        assert_eq!(TreeNode::from_paren_string("").unwrap_err(), ParseError::UnexpectedEnd);
+        assert_eq!(TreeNode::from_paren_string("1(2").unwrap_err(), ParseError::UnexpectedEnd);
+        assert_eq!(TreeNode::from_paren_string("1(").unwrap_err(), ParseError::UnexpectedEnd);
+        assert_eq!(TreeNode::from_paren_string("1)").unwrap_err(), ParseError::UnexpectedChar(1, ')'));
+        assert_eq!(TreeNode::from_paren_string("1(2))").unwrap_err(), ParseError::UnexpectedChar(4, ')'));
+        assert_eq!(TreeNode::from_paren_string("1()").unwrap_err(), ParseError::UnexpectedChar(2, ')'));
+        assert_eq!(TreeNode::from_paren_string("1 (2)").unwrap_err(), ParseError::UnexpectedChar(1, ' '));
+        assert_eq!(TreeNode::from_paren_string("(1)").unwrap_err(), ParseError::UnexpectedChar(0, '('));
+        assert_eq!(TreeNode::from_paren_string("1(-)").unwrap_err(), ParseError::UnexpectedChar(3, ')'));
+        assert_eq!(TreeNode::from_paren_string("1(x)").unwrap_err(), ParseError::UnexpectedChar(2, 'x'));
+        assert_eq!(TreeNode::from_paren_string("1(99999999999)").unwrap_err(), ParseError::InvalidNumber(2));
+    }
+
+    #[test]
+    fn test_paren_string_deep() {
+        let root = TreeNode::new(0);
+        let mut node = Rc::clone(&root);
+        for _ in 1..100_000 {
+            node = node.add_child(0);
+        }
+        let text = root.to_paren_string();
+        assert_eq!(TreeNode::from_paren_string(&text).unwrap().height(), 99_999);
+    }
+
+    /// Count how many values have been dropped.
+    struct Counted<'a>(&'a Cell<usize>);
+
+    impl Drop for Counted<'_> {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn test_drop_whole_tree() {
+        let dropped = Cell::new(0);
+        let root = TreeNode::new(Counted(&dropped));
+        for _ in 0..3 {
+            let child = root.add_child(Counted(&dropped));
+            child.add_child(Counted(&dropped)).add_child(Counted(&dropped));
+        }
+        assert_eq!(root.size(), 10);
+        // 子节点对父节点是弱引用，不会形成引用环
+        drop(root);
+        assert_eq!(dropped.get(), 10);
+
+        // 外部仍持有的子树在句柄释放后才被释放
+        let dropped = Cell::new(0);
+        let root = TreeNode::new(Counted(&dropped));
+        let child = root.add_child(Counted(&dropped));
+        child.add_child(Counted(&dropped));
+        drop(root);
+        assert_eq!(dropped.get(), 1);
+        assert!(child.parent().is_none());
+        drop(child);
+        assert_eq!(dropped.get(), 3);
+    }
+
+    #[test]
+    fn test_dfs_preorder() {
+        assert_eq!(values(&sample().dfs_preorder()), vec![1, 2, 5, 6, 3, 4, 7, 8]);
+        assert_eq!(values(&TreeNode::new(1).dfs_preorder()), vec![1]);
+    }
+
+    #[test]
+    fn test_find() {
+        let root = sample();
+        let eight = root.find(|&v| v == 8).unwrap();
+        assert_eq!(*eight.value(), 8);
+        assert_eq!(*eight.parent().unwrap().value(), 7);
+
+        assert!(Rc::ptr_eq(&root.find(|&v| v == 1).unwrap(), &root));
+        assert!(root.find(|&v| v == 9).is_none());
+
+        // 先序中 5 在 3 之前
+        assert_eq!(*root.find(|&v| v == 3 || v == 5).unwrap().value(), 5);
+        // 只在子树中查找
+        assert!(root.children()[0].find(|&v| v == 8).is_none());
+    }
+
+    #[test]
+    fn test_count_matching() {
+        let root = sample();
+        assert_eq!(root.count_matching(|&v| v % 2 == 0), 4);
+        assert_eq!(root.count_matching(|&v| v > 4), 4);
+        assert_eq!(root.children()[2].count_matching(|&v| v > 4), 2);
+        assert_eq!(root.count_matching(|_| true), 8);
+        assert_eq!(root.count_matching(|_| false), 0);
+    }
+
+    #[test]
+    fn test_bfs() {
+        assert_eq!(values(&sample().bfs()), vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        // 从子树开始只遍历该子树
+        let root = sample();
+        assert_eq!(values(&root.children()[2].bfs()), vec![4, 7, 8]);
+    }
+
+    #[test]
+    fn test_bfs_single_node() {
+        assert_eq!(values(&TreeNode::new(1).bfs()), vec![1]);
+    }
+
+    #[test]
+    fn test_dfs_preorder_deep() {
+        // 退化成链表的树，递归实现会栈溢出
+        let root = TreeNode::new(0);
+        let mut node = Rc::clone(&root);
+        for i in 1..100_000 {
+            node = node.add_child(i);
+        }
+        let order = root.dfs_preorder();
+        assert_eq!(order.len(), 100_000);
+        assert!(values(&order).into_iter().eq(0..100_000));
+    }
+}
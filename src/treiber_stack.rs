@@ -0,0 +1,231 @@
+//! Treiber lock-free stack
+
+use std::{
+    mem::ManuallyDrop,
+    ptr,
+    sync::atomic::{AtomicPtr, AtomicUsize, Ordering},
+};
+
+struct Node<T> {
+    // pop 取走值之后节点可能还要等一段时间才释放，所以释放节点时不再析构值
+    value: ManuallyDrop<T>,
+    // 等待释放的节点也通过 next 串成链表
+    next: AtomicPtr<Node<T>>,
+}
+
+/// A lock-free stack, `push` and `pop` retry a `compare_exchange` on the head until it succeeds.
+///
+/// ABA and use after free: a `pop` reads `head.next` and then swaps `head` for it,
+/// so if the head node were freed and its address reused by a new push in between,
+/// the swap would succeed with a stale `next`; a `push` holding the old head has the same problem.
+/// To rule this out, a popped node is only freed when no other `push` or `pop` is running,
+/// otherwise it is kept in a pending list and freed later by a thread that pops while it is alone.
+/// A node address is therefore never reused while some thread may still hold it.
+/// Under constant contention the pending list keeps growing until the stack is quiet.
+pub struct TreiberStack<T> {
+    head: AtomicPtr<Node<T>>,
+    // 正在 push 或 pop 的线程数
+    active: AtomicUsize,
+    // 已经出栈但还不能释放的节点
+    pending: AtomicPtr<Node<T>>,
+}
+
+unsafe impl<T: Send> Send for TreiberStack<T> {}
+unsafe impl<T: Send> Sync for TreiberStack<T> {}
+
+impl<T> TreiberStack<T> {
+    pub const fn new() -> Self {
+        Self {
+            head: AtomicPtr::new(ptr::null_mut()),
+            active: AtomicUsize::new(0),
+            pending: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    pub fn push(&self, value: T) {
+        let node = Box::into_raw(Box::new(Node {
+            value: ManuallyDrop::new(value),
+            next: AtomicPtr::new(ptr::null_mut()),
+        }));
+        // push 持有的 head 也不能被释放，否则地址复用后 CAS 会成功，next 却指向已释放的节点
+        self.active.fetch_add(1, Ordering::Acquire);
+        let mut head = self.head.load(Ordering::Acquire);
+        loop {
+            unsafe { (*node).next.store(head, Ordering::Relaxed) };
+            // Release 保证其他线程 pop 到这个节点时能看到它的值和 next
+            match self.head.compare_exchange_weak(head, node, Ordering::Release, Ordering::Acquire) {
+                Ok(_) => break,
+                Err(current) => head = current,
+            }
+        }
+        self.active.fetch_sub(1, Ordering::Release);
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        // Acquire 与最后释放节点的线程同步，保证看到它出栈的结果
+        self.active.fetch_add(1, Ordering::Acquire);
+        let mut head = self.head.load(Ordering::Acquire);
+        loop {
+            if head.is_null() {
+                self.active.fetch_sub(1, Ordering::Release);
+                return None;
+            }
+            // head 可能已经被其他线程出栈，但在本线程离开之前不会被释放，所以可以读
+            let next = unsafe { (*head).next.load(Ordering::Relaxed) };
+            match self.head.compare_exchange_weak(head, next, Ordering::Acquire, Ordering::Acquire) {
+                Ok(_) => break,
+                Err(current) => head = current,
+            }
+        }
+
+        let value = unsafe { ptr::read(&*(*head).value) };
+        unsafe { self.reclaim(head) };
+        Some(value)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire).is_null()
+    }
+
+    /// Free `node` popped by this thread, or defer it if other threads are in `push` or `pop`,
+    /// and leave `pop`.
+    unsafe fn reclaim(&self, node: *mut Node<T>) {
+        // 仍在 pop 中时接管等待释放的链表，之后再加入的节点不属于这一批
+        let pending = self.pending.swap(ptr::null_mut(), Ordering::Acquire);
+        // 用 CAS 离开 pop：成功说明其他线程都已离开，并且与它们的离开同步，
+        // 它们读取这些节点都发生在释放之前；之后进入的线程只能读到栈中的节点
+        if self
+            .active
+            .compare_exchange(1, 0, Ordering::AcqRel, Ordering::Relaxed)
+            .is_ok()
+        {
+            Self::free_list(pending);
+            drop(Box::from_raw(node));
+        } else {
+            // 其他线程可能还持有这些节点，交给之后最后离开的线程释放
+            node_set_next(node, pending);
+            self.defer_list(node);
+            self.active.fetch_sub(1, Ordering::Release);
+        }
+    }
+
+    /// Prepend the list starting at `first` to the pending list.
+    unsafe fn defer_list(&self, first: *mut Node<T>) {
+        let mut last = first;
+        loop {
+            let next = (*last).next.load(Ordering::Relaxed);
+            if next.is_null() {
+                break;
+            }
+            last = next;
+        }
+        let mut pending = self.pending.load(Ordering::Relaxed);
+        loop {
+            node_set_next(last, pending);
+            match self.pending.compare_exchange_weak(pending, first, Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => return,
+                Err(current) => pending = current,
+            }
+        }
+    }
+
+    /// Free the nodes of a list whose values have been taken.
+    unsafe fn free_list(mut node: *mut Node<T>) {
+        while !node.is_null() {
+            let next = (*node).next.load(Ordering::Relaxed);
+            drop(Box::from_raw(node));
+            node = next;
+        }
+    }
+}
+
+unsafe fn node_set_next<T>(node: *mut Node<T>, next: *mut Node<T>) {
+    (*node).next.store(next, Ordering::Relaxed);
+}
+
+impl<T> Default for TreiberStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for TreiberStack<T> {
+    fn drop(&mut self) {
+        // 此时没有其他线程访问，栈中的值还需要析构，等待释放的节点只需要释放内存
+        let mut node = *self.head.get_mut();
+        while !node.is_null() {
+            unsafe {
+                let mut boxed = Box::from_raw(node);
+                ManuallyDrop::drop(&mut boxed.value);
+                node = *boxed.next.get_mut();
+            }
+        }
+        unsafe { Self::free_list(*self.pending.get_mut()) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{rc::Rc, sync::Arc, thread};
+    use super::TreiberStack;
+
+    #[test]
+    fn test_treiber_stack() {
+        let stack = TreiberStack::new();
+        assert!(stack.is_empty());
+        assert_eq!(stack.pop(), None);
+        stack.push(1);
+        stack.push(2);
+        stack.push(3);
+        assert!(!stack.is_empty());
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(2));
+        stack.push(4);
+        assert_eq!(stack.pop(), Some(4));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), None);
+    }
+
+    #[test]
+    fn test_drop_remaining() {
+        let value = Rc::new(0);
+        let stack = TreiberStack::new();
+        for _ in 0..10 {
+            stack.push(Rc::clone(&value));
+        }
+        drop(stack.pop());
+        assert_eq!(Rc::strong_count(&value), 10);
+        // 栈中剩余的值随栈一起析构
+        drop(stack);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn test_concurrent_push_pop() {
+        let stack = Arc::new(TreiberStack::new());
+        let threads: Vec<_> = (0..4).map(|t| {
+            let stack = stack.clone();
+            thread::spawn(move || {
+                let mut popped = Vec::new();
+                for i in 0..10_000 {
+                    stack.push(t * 10_000 + i);
+                    if i % 2 == 0 {
+                        popped.extend(stack.pop());
+                    }
+                }
+                popped
+            })
+        }).collect();
+
+        let mut all: Vec<usize> = threads
+            .into_iter()
+            .flat_map(|t| t.join().expect("Couldn't join on the associated thread"))
+            .collect();
+        while let Some(value) = stack.pop() {
+            all.push(value);
+        }
+        // 每个值恰好出栈一次
+        all.sort();
+        assert_eq!(all, (0..40_000).collect::<Vec<usize>>());
+    }
+}
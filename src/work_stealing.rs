@@ -0,0 +1,328 @@
+//! Chase-Lev work-stealing deque
+
+use std::{
+    cell::UnsafeCell,
+    ptr,
+    sync::{atomic::{fence, AtomicIsize, AtomicPtr, Ordering}, Arc},
+    thread,
+};
+
+/// Circular buffer of slots, indexed by the positions of the deque modulo its capacity.
+/// Each slot holds a boxed value, so a stealer racing with the owner reusing the slot
+/// only loads a pointer atomically, and the value is read after it wins the position.
+struct Buffer<T> {
+    slots: Box<[AtomicPtr<T>]>,
+    // It must be a power of two.
+    capacity: usize,
+}
+
+impl<T> Buffer<T> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            slots: (0..capacity).map(|_| AtomicPtr::new(ptr::null_mut())).collect(),
+            capacity,
+        }
+    }
+
+    fn slot(&self, position: isize) -> &AtomicPtr<T> {
+        &self.slots[position as usize & (self.capacity - 1)]
+    }
+
+    fn write(&self, position: isize, value: *mut T) {
+        self.slot(position).store(value, Ordering::Relaxed);
+    }
+
+    /// The pointer may be replaced by the owner reusing the slot, so it is only owned if the caller
+    /// then wins the position.
+    fn read(&self, position: isize) -> *mut T {
+        self.slot(position).load(Ordering::Relaxed)
+    }
+}
+
+struct Inner<T> {
+    // 窃取端从 top 取出，只会增加
+    top: AtomicIsize,
+    // 所有者在 bottom 放入和取出
+    bottom: AtomicIsize,
+    buffer: AtomicPtr<Buffer<T>>,
+    // 扩容后旧的缓冲区可能还在被窃取端读取，一直保留到 Inner 释放，只有所有者会访问
+    retired: UnsafeCell<Vec<*mut Buffer<T>>>,
+}
+
+unsafe impl<T: Send> Send for Inner<T> {}
+unsafe impl<T: Send> Sync for Inner<T> {}
+
+impl<T> Drop for Inner<T> {
+    fn drop(&mut self) {
+        let top = *self.top.get_mut();
+        let bottom = *self.bottom.get_mut();
+        let buffer = unsafe { Box::from_raw(*self.buffer.get_mut()) };
+        // 只有 [top, bottom) 中的值还在，旧缓冲区中的值已经搬到了新的缓冲区
+        for position in top..bottom {
+            drop(unsafe { Box::from_raw(buffer.read(position)) });
+        }
+        for &old in self.retired.get_mut().iter() {
+            drop(unsafe { Box::from_raw(old) });
+        }
+    }
+}
+
+/// The owner's side of a work-stealing deque: it pushes and pops at the bottom like a stack,
+/// while any number of `Stealer`s take the oldest elements from the top.
+/// The buffer doubles when it is full, old buffers are kept until the deque and all stealers
+/// are dropped.
+///
+/// Orderings, following the C11 version of Lê et al.:
+/// `push` boxes the value and stores the pointer in the slot, then a `Release` fence before
+/// bumping `bottom`, so a stealer that loads the new `bottom` with `Acquire` sees the value.
+/// Growing publishes the new buffer with a `Release`.
+/// `pop` decrements `bottom` before loading `top` and `steal` loads `top` before `bottom`,
+/// each with a `SeqCst` fence in between, so for the last element at least one of them sees the
+/// other and they race on a `SeqCst` CAS of `top` instead of both taking it.
+/// Every successful steal moves `top` with that CAS, so a position is taken at most once.
+pub struct Deque<T> {
+    inner: Arc<Inner<T>>,
+}
+
+/// The stealing side of a `Deque`, clone it for more threads.
+pub struct Stealer<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> Deque<T> {
+    const MIN_CAPACITY: usize = 16;
+
+    pub fn new() -> Self {
+        let buffer = Box::into_raw(Box::new(Buffer::new(Self::MIN_CAPACITY)));
+        Self {
+            inner: Arc::new(Inner {
+                top: AtomicIsize::new(0),
+                bottom: AtomicIsize::new(0),
+                buffer: AtomicPtr::new(buffer),
+                retired: UnsafeCell::new(Vec::new()),
+            }),
+        }
+    }
+
+    pub fn stealer(&self) -> Stealer<T> {
+        Stealer { inner: self.inner.clone() }
+    }
+
+    /// Push the value at the bottom, growing the buffer if it is full.
+    pub fn push(&mut self, value: T) {
+        let inner = &*self.inner;
+        let bottom = inner.bottom.load(Ordering::Relaxed);
+        let top = inner.top.load(Ordering::Acquire);
+        let mut buffer = inner.buffer.load(Ordering::Relaxed);
+        // top 只会增加，读到旧的 top 只会提前扩容
+        if bottom - top >= unsafe { (*buffer).capacity } as isize {
+            buffer = unsafe { self.grow(top, bottom) };
+        }
+        unsafe { (*buffer).write(bottom, Box::into_raw(Box::new(value))) };
+        fence(Ordering::Release);
+        inner.bottom.store(bottom + 1, Ordering::Relaxed);
+    }
+
+    /// Pop the value pushed last, `None` if it is empty or a stealer took the last one.
+    pub fn pop(&mut self) -> Option<T> {
+        let inner = &*self.inner;
+        let bottom = inner.bottom.load(Ordering::Relaxed) - 1;
+        let buffer = inner.buffer.load(Ordering::Relaxed);
+        // 先占住 bottom 再看 top，窃取端之后就不会越过 bottom
+        inner.bottom.store(bottom, Ordering::Relaxed);
+        fence(Ordering::SeqCst);
+        let top = inner.top.load(Ordering::Relaxed);
+
+        if top > bottom {
+            // 已经空了，恢复 bottom
+            inner.bottom.store(bottom + 1, Ordering::Relaxed);
+            return None;
+        }
+
+        let value = unsafe { (*buffer).read(bottom) };
+        if top < bottom {
+            // 至少还剩两个，窃取端拿不到 bottom 这个位置
+            return Some(*unsafe { Box::from_raw(value) });
+        }
+
+        // 只剩最后一个，和窃取端抢 top
+        let won = inner
+            .top
+            .compare_exchange(top, top + 1, Ordering::SeqCst, Ordering::Relaxed)
+            .is_ok();
+        inner.bottom.store(bottom + 1, Ordering::Relaxed);
+        // 输了说明值已经属于窃取端，由它来释放
+        if won {
+            Some(*unsafe { Box::from_raw(value) })
+        } else {
+            None
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        let bottom = self.inner.bottom.load(Ordering::Relaxed);
+        let top = self.inner.top.load(Ordering::Relaxed);
+        (bottom - top).max(0) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Move `[top, bottom)` into a buffer twice as large and retire the old one.
+    /// Only called by `push`, so `retired` is accessed by the owner alone.
+    unsafe fn grow(&self, top: isize, bottom: isize) -> *mut Buffer<T> {
+        let inner = &*self.inner;
+        let old = inner.buffer.load(Ordering::Relaxed);
+        let new = Box::into_raw(Box::new(Buffer::new((*old).capacity * 2)));
+        // 按位置复制指针，窃取端在新旧缓冲区中读到的是同一个值
+        for position in top..bottom {
+            (*new).write(position, (*old).read(position));
+        }
+        inner.buffer.store(new, Ordering::Release);
+        (*inner.retired.get()).push(old);
+        new
+    }
+}
+
+impl<T> Default for Deque<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Stealer<T> {
+    /// Take the oldest value, `None` if it is empty.
+    /// Losing a race with another stealer or the owner retries, so `None` only means empty.
+    pub fn steal(&self) -> Option<T> {
+        let inner = &*self.inner;
+        loop {
+            let top = inner.top.load(Ordering::Acquire);
+            fence(Ordering::SeqCst);
+            let bottom = inner.bottom.load(Ordering::Acquire);
+            if top >= bottom {
+                return None;
+            }
+
+            let buffer = inner.buffer.load(Ordering::Acquire);
+            let value = unsafe { (*buffer).read(top) };
+            if inner
+                .top
+                .compare_exchange(top, top + 1, Ordering::SeqCst, Ordering::Relaxed)
+                .is_ok()
+            {
+                return Some(*unsafe { Box::from_raw(value) });
+            }
+            // 这个位置被别人拿走了，指针不属于自己，不能释放，直接重试
+            thread::yield_now();
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        let top = self.inner.top.load(Ordering::Acquire);
+        let bottom = self.inner.bottom.load(Ordering::Acquire);
+        top >= bottom
+    }
+}
+
+impl<T> Clone for Stealer<T> {
+    fn clone(&self) -> Self {
+        Self { inner: self.inner.clone() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        rc::Rc,
+        sync::{atomic::{AtomicBool, Ordering}, Arc},
+        thread,
+    };
+    use super::Deque;
+
+    #[test]
+    fn test_deque() {
+        let mut deque = Deque::new();
+        let stealer = deque.stealer();
+        assert_eq!(deque.pop(), None);
+        assert_eq!(stealer.steal(), None);
+        for i in 0..100 {
+            deque.push(i);
+        }
+        // 超过初始容量后扩容
+        assert_eq!(deque.len(), 100);
+        // 所有者后进先出，窃取端先进先出
+        assert_eq!(deque.pop(), Some(99));
+        assert_eq!(stealer.steal(), Some(0));
+        assert_eq!(stealer.steal(), Some(1));
+        assert_eq!(deque.pop(), Some(98));
+        assert_eq!(deque.len(), 96);
+
+        while deque.pop().is_some() {}
+        assert!(deque.is_empty());
+        assert!(stealer.is_empty());
+        assert_eq!(stealer.steal(), None);
+    }
+
+    #[test]
+    fn test_drop_remaining() {
+        let value = Rc::new(0);
+        let mut deque = Deque::new();
+        for _ in 0..40 {
+            deque.push(Rc::clone(&value));
+        }
+        let stealer = deque.stealer();
+        drop(stealer.steal());
+        drop(deque.pop());
+        assert_eq!(Rc::strong_count(&value), 39);
+        // 最后一个句柄释放时析构剩余的值
+        drop(deque);
+        assert_eq!(Rc::strong_count(&value), 39);
+        drop(stealer);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn test_steal_exactly_once() {
+        let mut deque = Deque::new();
+        let done = Arc::new(AtomicBool::new(false));
+
+        let stealers: Vec<_> = (0..3).map(|_| {
+            let stealer = deque.stealer();
+            let done = done.clone();
+            thread::spawn(move || {
+                let mut stolen = Vec::new();
+                loop {
+                    match stealer.steal() {
+                        Some(task) => stolen.push(task),
+                        // 所有者取完之后才设置 done，此时不会再有任务
+                        None if done.load(Ordering::Acquire) => break,
+                        None => thread::yield_now(),
+                    }
+                }
+                stolen
+            })
+        }).collect();
+
+        let mut executed = Vec::new();
+        // Miri 下线程交错很慢，减少任务数
+        let count = if cfg!(miri) { 200 } else { 20_000 };
+        for i in 0..count {
+            deque.push(i);
+            if i % 3 == 0 {
+                executed.extend(deque.pop());
+            }
+        }
+        while let Some(task) = deque.pop() {
+            executed.push(task);
+        }
+        done.store(true, Ordering::Release);
+
+        for stealer in stealers {
+            executed.extend(stealer.join().expect("Couldn't join on the associated thread"));
+        }
+        // 每个任务恰好执行一次
+        executed.sort();
+        assert_eq!(executed, (0..count).collect::<Vec<usize>>());
+    }
+}